@@ -0,0 +1,361 @@
+use crate::imports::*;
+
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+// maximum accepted admin request body size
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Admin requests are authorized using the status page session
+/// cookie or `Authorization: Bearer <passphrase>` header.
+pub fn is_authorized(resolver: &Arc<Resolver>, req: &Request<Body>) -> bool {
+    if status::session_from_req(resolver, req).is_some() {
+        return true;
+    }
+
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(status::validate_passphrase)
+}
+
+pub async fn read_json<T: DeserializeOwned>(req: Request<Body>) -> Result<T> {
+    let bytes = axum::body::to_bytes(req.into_body(), MAX_BODY_SIZE)
+        .await
+        .map_err(|_| Error::Http(StatusCode::BAD_REQUEST, "Bad request"))?;
+    serde_json::from_slice(&bytes).map_err(|_| Error::Http(StatusCode::BAD_REQUEST, "Bad request"))
+}
+
+pub fn with_json(status: StatusCode, json: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(
+                "no-cache, no-store, must-revalidate, proxy-revalidate, max-age=0",
+            ),
+        )
+        .body(Body::from(json))
+        .unwrap()
+}
+
+pub fn with_error(error: Error) -> Response<Body> {
+    let status = match &error {
+        Error::Http(status, _) => *status,
+        Error::Unauthorized => StatusCode::UNAUTHORIZED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let json = serde_json::json!({ "error": error.to_string() }).to_string();
+    with_json(status, json)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DrainRequest {
+    #[serde(flatten)]
+    pub target: DrainTarget,
+    pub drain: bool,
+    pub by: Option<String>,
+}
+
+pub async fn drain_list_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if !is_authorized(resolver, &req) {
+        return with_error(Error::Unauthorized);
+    }
+
+    match resolver.drains().to_json() {
+        Ok(json) => with_json(StatusCode::OK, json),
+        Err(err) => with_error(err),
+    }
+}
+
+pub async fn drain_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if !is_authorized(resolver, &req) {
+        return with_error(Error::Unauthorized);
+    }
+
+    let result = async {
+        let DrainRequest { target, drain, by } = read_json::<DrainRequest>(req).await?;
+        let by = by.unwrap_or_else(|| "admin".to_string());
+        resolver.drains().set(target, drain, &by)?;
+        resolver.drains().to_json()
+    }
+    .await;
+
+    match result {
+        Ok(json) => with_json(StatusCode::OK, json),
+        Err(err) => with_error(err),
+    }
+}
+
+pub async fn reload_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if !is_authorized(resolver, &req) {
+        return with_error(Error::Unauthorized);
+    }
+
+    match resolver.schedule_reload().await {
+        Ok(()) => with_json(StatusCode::OK, "{}".to_string()),
+        Err(err) => with_error(err),
+    }
+}
+
+/// Update source and package hash currently in use
+/// (`null` if the config was not obtained via updates).
+pub async fn update_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if !is_authorized(resolver, &req) {
+        return with_error(Error::Unauthorized);
+    }
+
+    match serde_json::to_string(&sources::active()) {
+        Ok(json) => with_json(StatusCode::OK, json),
+        Err(err) => with_error(err.into()),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PinRequest {
+    // package hash (prefix) or history position, `null` clears the pin
+    pub package: Option<String>,
+}
+
+/// Config history along with the pinned package hash.
+pub async fn history_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if !is_authorized(resolver, &req) {
+        return with_error(Error::Unauthorized);
+    }
+
+    match history::history().and_then(|history| Ok(serde_json::to_string(&history)?)) {
+        Ok(json) => with_json(StatusCode::OK, json),
+        Err(err) => with_error(err),
+    }
+}
+
+/// Pins a package from the config history (or clears the pin).
+/// The pinned package is applied on the next update.
+pub async fn pin_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if !is_authorized(resolver, &req) {
+        return with_error(Error::Unauthorized);
+    }
+
+    let result = async {
+        let PinRequest { package } = read_json::<PinRequest>(req).await?;
+        match package {
+            Some(package) => {
+                history::pin(&package)?;
+            }
+            None => history::unpin()?,
+        }
+        resolver.schedule_update().await?;
+        Ok::<_, Error>(serde_json::to_string(&history::history()?)?)
+    }
+    .await;
+
+    match result {
+        Ok(json) => with_json(StatusCode::OK, json),
+        Err(err) => with_error(err),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApproveRequest {
+    pub id: String,
+    pub approve: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenRequest {
+    pub operator: String,
+    pub revoke: Option<bool>,
+}
+
+/// Node registration by an operator authenticated with
+/// `Authorization: Bearer <token>`.
+pub async fn register_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    let operator = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|token| resolver.registry().authenticate(token));
+    let Some(operator) = operator else {
+        return with_error(Error::Unauthorized);
+    };
+
+    let result = async {
+        let request = read_json::<RegistrationRequest>(req).await?;
+        if resolver.is_configured(&request.address) {
+            return Err(Error::Http(
+                StatusCode::CONFLICT,
+                "Node address is already configured",
+            ));
+        }
+        let registration = resolver.registry().register(&operator, request).await?;
+        Ok::<_, Error>(serde_json::to_string(&registration)?)
+    }
+    .await;
+
+    match result {
+        Ok(json) => with_json(StatusCode::OK, json),
+        Err(err) => with_error(err),
+    }
+}
+
+/// Registered operators and node registrations.
+pub async fn registry_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if !is_authorized(resolver, &req) {
+        return with_error(Error::Unauthorized);
+    }
+
+    match resolver.registry().to_json() {
+        Ok(json) => with_json(StatusCode::OK, json),
+        Err(err) => with_error(err),
+    }
+}
+
+/// Approves (or removes) a node registration.
+pub async fn approve_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if !is_authorized(resolver, &req) {
+        return with_error(Error::Unauthorized);
+    }
+
+    let result = async {
+        let ApproveRequest { id, approve } = read_json::<ApproveRequest>(req).await?;
+        resolver.registry().approve(&id, approve)?;
+        resolver.schedule_refresh().await?;
+        resolver.registry().to_json()
+    }
+    .await;
+
+    match result {
+        Ok(json) => with_json(StatusCode::OK, json),
+        Err(err) => with_error(err),
+    }
+}
+
+/// Issues (or revokes) an operator registration token.
+pub async fn token_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if !is_authorized(resolver, &req) {
+        return with_error(Error::Unauthorized);
+    }
+
+    let result = async {
+        let TokenRequest { operator, revoke } = read_json::<TokenRequest>(req).await?;
+        if revoke.unwrap_or(false) {
+            resolver.registry().revoke(&operator)?;
+            resolver.schedule_refresh().await?;
+            Ok::<_, Error>(serde_json::json!({ "operator": operator }).to_string())
+        } else {
+            let token = resolver.registry().issue_token(&operator)?;
+            Ok(serde_json::json!({ "operator": operator, "token": token }).to_string())
+        }
+    }
+    .await;
+
+    match result {
+        Ok(json) => with_json(StatusCode::OK, json),
+        Err(err) => with_error(err),
+    }
+}
+
+/// Effective node list with group attribution
+/// (`?format=json|toml|csv`, default `json`).
+pub async fn config_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if !is_authorized(resolver, &req) {
+        return with_error(Error::Unauthorized);
+    }
+
+    let nodes = resolver
+        .connections()
+        .iter()
+        .map(|connection| connection.node().clone())
+        .collect::<Vec<_>>();
+
+    let result = req
+        .uri()
+        .query()
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .find_map(|param| param.strip_prefix("format="))
+        .map(export::Format::from_str)
+        .unwrap_or(Ok(export::Format::default()))
+        .map_err(|_| Error::Http(StatusCode::BAD_REQUEST, "Unknown format"))
+        .and_then(|format| Ok((format, export::render(&nodes, format)?)));
+
+    match result {
+        Ok((format, body)) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, format.mime())
+            .header(
+                header::CACHE_CONTROL,
+                HeaderValue::from_static(
+                    "no-cache, no-store, must-revalidate, proxy-revalidate, max-age=0",
+                ),
+            )
+            .body(Body::from(body))
+            .unwrap(),
+        Err(err) => with_error(err),
+    }
+}
+
+/// Issues an authenticated admin request to a running
+/// resolver instance listening on `listen` (CLI).
+pub async fn request<T: Serialize>(listen: &str, path: &str, body: &T) -> Result<String> {
+    let passphrase = cliclack::password("Enter password:").interact()?;
+
+    let url = format!("http://{listen}{path}");
+    let response = reqwest::Client::new()
+        .post(&url)
+        .bearer_auth(passphrase.trim())
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(body)?)
+        .send()
+        .await?;
+
+    let status = response.status();
+    let text = response.text().await?;
+    if status.is_success() {
+        Ok(text)
+    } else {
+        Err(Error::custom(format!("{status}: {text}")))
+    }
+}
+
+/// Issues a drain request to a running resolver instance (CLI).
+pub async fn drain(listen: &str, target: DrainTarget, drain: bool) -> Result<()> {
+    let body = DrainRequest {
+        target,
+        drain,
+        by: Some(operator()),
+    };
+
+    request(listen, "/admin/drain", &body).await?;
+    if drain {
+        log::success(format!("Draining {target}"))?;
+    } else {
+        log::success(format!("Drain cleared for {target}"))?;
+    }
+    Ok(())
+}
+
+/// Issues a config reload request to a running resolver instance (CLI).
+pub async fn reload(listen: &str) -> Result<()> {
+    request(listen, "/admin/reload", &serde_json::json!({})).await?;
+    log::success("Configuration reload scheduled")?;
+    Ok(())
+}
+
+/// Operator identifier used in CLI admin requests (`user@host`).
+fn operator() -> String {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string());
+    let host = std::env::var("HOSTNAME").ok();
+    match host {
+        Some(host) => format!("{user}@{host}"),
+        None => user,
+    }
+}
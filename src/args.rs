@@ -2,15 +2,30 @@ pub use clap::{ArgAction, Parser};
 use std::path::PathBuf;
 use std::str::FromStr;
 
+use crate::drain::DrainTarget;
+use crate::export::Format;
 use crate::{log_error, log_success};
 
 #[derive(Debug)]
 pub enum Action {
     Login,
+    Publisher,
+    Identity,
     Pack,
+    AddRecipient { key: String, name: Option<String> },
+    RemoveRecipient { key: String },
+    Recipients,
     Unpack,
-    Update,
+    Migrate { from: u64, to: u64, force: bool },
+    Update { dry_run: bool },
+    History { unpin: bool },
+    Rollback { package: String },
     Test,
+    Export { format: Format },
+    Drain { target: DrainTarget, drain: bool },
+    Reload,
+    Settings,
+    Schema,
     Run,
 }
 
@@ -30,6 +45,10 @@ pub struct Args {
     pub auto_update: bool,
     /// Custom config file
     pub user_config: Option<PathBuf>,
+    /// Custom settings file
+    pub settings: Option<PathBuf>,
+    /// Setting overrides in the form `<key>=<value>`
+    pub set: Vec<String>,
     /// public status page
     pub public: bool,
     // Show node data on each election
@@ -50,9 +69,7 @@ impl Args {
         use clap::{arg, command, Arg, Command};
 
         let cmd = Command::new("kaspa-resolver")
-            .about(format!(
-                "resolver v{}", crate::VERSION
-            ))
+            .about(format!("resolver v{}", crate::VERSION))
             .arg(arg!(--version "Display software version"))
             .arg(arg!(--verbose "Enable verbose logging"))
             .arg(arg!(--public "Enable public status page"))
@@ -91,13 +108,149 @@ impl Args {
                     .require_equals(true)
                     .help("Listen on custom interface and port [default: 127.0.0.1:8989]"),
             )
+            .arg(
+                Arg::new("settings")
+                    .long("settings")
+                    .value_name("settings.toml file")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("TOML settings file [default: ~/.kaspa-resolver/settings.toml]"),
+            )
+            .arg(
+                Arg::new("set")
+                    .long("set")
+                    .value_name("KEY=VALUE")
+                    .num_args(1)
+                    .action(ArgAction::Append)
+                    .help("Override a setting (e.g. `--set sync.poll-sec=2.0`)"),
+            )
             .subcommand(Command::new("test").about("Test configuration"))
+            .subcommand(
+                Command::new("export")
+                    .about("Export the effective node list")
+                    .arg(
+                        Arg::new("format")
+                            .long("format")
+                            .value_name("json|toml|csv")
+                            .num_args(1)
+                            .default_value("json")
+                            .help("Output format"),
+                    ),
+            )
+            .subcommand(Command::new("settings").about("Display effective settings"))
+            .subcommand(Command::new("schema").about("Display config JSON Schema"))
             .subcommand(Command::new("login").about("Create local update key"))
-            .subcommand(Command::new("pack").about("Pack configuration"))
+            .subcommand(Command::new("publisher").about("Create package publisher signing key"))
+            .subcommand(Command::new("identity").about("Create package recipient identity key"))
+            .subcommand(
+                Command::new("pack")
+                    .about("Pack and sign configuration")
+                    .subcommand(
+                        Command::new("add-recipient")
+                            .about("Add package recipient public key")
+                            .arg(
+                                Arg::new("key")
+                                    .required(true)
+                                    .help("Recipient public key (hex)"),
+                            )
+                            .arg(
+                                Arg::new("name")
+                                    .long("name")
+                                    .num_args(1)
+                                    .help("Recipient name"),
+                            ),
+                    )
+                    .subcommand(
+                        Command::new("remove-recipient")
+                            .about("Remove package recipient")
+                            .arg(
+                                Arg::new("key")
+                                    .required(true)
+                                    .help("Recipient public key (hex) or name"),
+                            ),
+                    )
+                    .subcommand(Command::new("recipients").about("List package recipients")),
+            )
             .subcommand(Command::new("unpack").about("Unpack configuration"))
-            .subcommand(Command::new("update").about("Update configuration from GitHub"))
-            // .subcommand(Command::new("reload").about("Reload configuration"))
-        ;
+            .subcommand(
+                Command::new("migrate")
+                    .about("Migrate configuration package to a newer schema version")
+                    .arg(
+                        Arg::new("from")
+                            .long("from")
+                            .value_name("VERSION")
+                            .num_args(1)
+                            .required(true)
+                            .value_parser(clap::value_parser!(u64))
+                            .help("Source package version"),
+                    )
+                    .arg(
+                        Arg::new("to")
+                            .long("to")
+                            .value_name("VERSION")
+                            .num_args(1)
+                            .value_parser(clap::value_parser!(u64))
+                            .help("Target package version [default: current version]"),
+                    )
+                    .arg(
+                        Arg::new("force")
+                            .long("force")
+                            .action(ArgAction::SetTrue)
+                            .help("Overwrite an existing target package"),
+                    ),
+            )
+            .subcommand(
+                Command::new("update")
+                    .about("Update configuration from GitHub")
+                    .arg(
+                        Arg::new("dry-run")
+                            .long("dry-run")
+                            .action(ArgAction::SetTrue)
+                            .help("Display configuration changes without applying them"),
+                    ),
+            )
+            .subcommand(
+                Command::new("history")
+                    .about("List previously applied configuration packages")
+                    .arg(
+                        Arg::new("unpin")
+                            .long("unpin")
+                            .action(ArgAction::SetTrue)
+                            .help("Clear the package pin and resume updates"),
+                    ),
+            )
+            .subcommand(
+                Command::new("rollback")
+                    .about("Reinstall and pin a previously applied configuration package")
+                    .arg(
+                        Arg::new("package")
+                            .required(true)
+                            .value_name("hash|n")
+                            .help(
+                            "Package hash (prefix) or history position (1 is the previous package)",
+                        ),
+                    ),
+            )
+            .subcommand(
+                Command::new("drain")
+                    .about("Set node drain state on a running resolver (via `--listen` address)")
+                    .arg(
+                        Arg::new("target")
+                            .required(true)
+                            .value_name("uid:<hex>|sid:<hex>")
+                            .help("Node uid or system id to drain"),
+                    )
+                    .arg(
+                        Arg::new("clear")
+                            .long("clear")
+                            .action(ArgAction::SetTrue)
+                            .help("Clear drain state"),
+                    ),
+            )
+            .subcommand(
+                Command::new("reload")
+                    .about("Reload configuration of a running resolver (via `--listen` address)"),
+            );
 
         let matches = cmd.get_matches();
 
@@ -117,24 +270,18 @@ impl Args {
         // let election = matches.get_one::<bool>("election").cloned().unwrap_or(false);
         // let status = matches.get_one::<bool>("status").cloned().unwrap_or(false);
 
-        let user_config = matches.get_one::<String>("config-file").cloned().map(|s| {
-            if s.contains('~') {
-                let s = s.replace(
-                    "~",
-                    dirs::home_dir()
-                        .expect("Unable to obtain user home folder")
-                        .to_str()
-                        .unwrap(),
-                );
-                PathBuf::from(s)
-            } else if !s.starts_with('/') {
-                std::env::current_dir()
-                    .expect("Unable to obtain current working directory")
-                    .join(s)
-            } else {
-                PathBuf::from(s)
-            }
-        });
+        let user_config = matches
+            .get_one::<String>("config-file")
+            .cloned()
+            .map(absolute_path);
+        let settings = matches
+            .get_one::<String>("settings")
+            .cloned()
+            .map(absolute_path);
+        let set = matches
+            .get_many::<String>("set")
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default();
 
         if let Some(user_config) = &user_config {
             log_success!(
@@ -163,14 +310,65 @@ impl Args {
             std::process::exit(0);
         } else if let Some(_matches) = matches.subcommand_matches("test") {
             Action::Test
+        } else if let Some(matches) = matches.subcommand_matches("export") {
+            let format = matches.get_one::<String>("format").unwrap();
+            let format = Format::from_str(format).unwrap_or_else(|err| {
+                log_error!("Export", "{err}");
+                std::process::exit(1);
+            });
+            Action::Export { format }
         } else if let Some(_matches) = matches.subcommand_matches("login") {
             Action::Login
-        } else if let Some(_matches) = matches.subcommand_matches("pack") {
-            Action::Pack
+        } else if let Some(_matches) = matches.subcommand_matches("publisher") {
+            Action::Publisher
+        } else if let Some(_matches) = matches.subcommand_matches("identity") {
+            Action::Identity
+        } else if let Some(matches) = matches.subcommand_matches("pack") {
+            if let Some(matches) = matches.subcommand_matches("add-recipient") {
+                let key = matches.get_one::<String>("key").cloned().unwrap();
+                let name = matches.get_one::<String>("name").cloned();
+                Action::AddRecipient { key, name }
+            } else if let Some(matches) = matches.subcommand_matches("remove-recipient") {
+                let key = matches.get_one::<String>("key").cloned().unwrap();
+                Action::RemoveRecipient { key }
+            } else if matches.subcommand_matches("recipients").is_some() {
+                Action::Recipients
+            } else {
+                Action::Pack
+            }
         } else if let Some(_matches) = matches.subcommand_matches("unpack") {
             Action::Unpack
-        } else if let Some(_matches) = matches.subcommand_matches("update") {
-            Action::Update
+        } else if let Some(matches) = matches.subcommand_matches("migrate") {
+            let from = matches.get_one::<u64>("from").cloned().unwrap();
+            let to = matches
+                .get_one::<u64>("to")
+                .cloned()
+                .unwrap_or(crate::config::VERSION);
+            let force = matches.get_one::<bool>("force").cloned().unwrap_or(false);
+            Action::Migrate { from, to, force }
+        } else if let Some(matches) = matches.subcommand_matches("update") {
+            let dry_run = matches.get_one::<bool>("dry-run").cloned().unwrap_or(false);
+            Action::Update { dry_run }
+        } else if let Some(matches) = matches.subcommand_matches("history") {
+            let unpin = matches.get_one::<bool>("unpin").cloned().unwrap_or(false);
+            Action::History { unpin }
+        } else if let Some(matches) = matches.subcommand_matches("rollback") {
+            let package = matches.get_one::<String>("package").cloned().unwrap();
+            Action::Rollback { package }
+        } else if let Some(_matches) = matches.subcommand_matches("reload") {
+            Action::Reload
+        } else if let Some(_matches) = matches.subcommand_matches("settings") {
+            Action::Settings
+        } else if let Some(_matches) = matches.subcommand_matches("schema") {
+            Action::Schema
+        } else if let Some(matches) = matches.subcommand_matches("drain") {
+            let target = matches.get_one::<String>("target").unwrap();
+            let target = DrainTarget::from_str(target).unwrap_or_else(|err| {
+                log_error!("Drain", "{err}");
+                std::process::exit(1);
+            });
+            let drain = !matches.get_one::<bool>("clear").cloned().unwrap_or(false);
+            Action::Drain { target, drain }
         } else {
             Action::Run
         };
@@ -181,6 +379,8 @@ impl Args {
             debug,
             auto_update,
             user_config,
+            settings,
+            set,
             public,
             // election,
             // status,
@@ -191,6 +391,26 @@ impl Args {
     }
 }
 
+// resolve path relative to the home folder (`~`) or the current working directory
+fn absolute_path(s: String) -> PathBuf {
+    if s.contains('~') {
+        let s = s.replace(
+            "~",
+            dirs::home_dir()
+                .expect("Unable to obtain user home folder")
+                .to_str()
+                .unwrap(),
+        );
+        PathBuf::from(s)
+    } else if !s.starts_with('/') {
+        std::env::current_dir()
+            .expect("Unable to obtain current working directory")
+            .join(s)
+    } else {
+        PathBuf::from(s)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct RateLimit {
     pub requests: u64,
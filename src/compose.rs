@@ -0,0 +1,297 @@
+//!
+//! Node sources. A [`NodeSource`] produces a list of nodes in response
+//! to resolver events (startup, scheduled update, user config reload,
+//! DNS discovery or registry refresh). The [`Composer`] retains the
+//! most recent list of each source and merges them in the order of
+//! precedence: nodes colliding with a node of a preceding source are
+//! ignored. Nodes are tagged with the [`Provenance`] of their source
+//! as the source list is retained; a node moving to another source is
+//! treated as a changed node and its connection is re-created.
+//!
+
+use crate::imports::*;
+use async_trait::async_trait;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Provenance {
+    // user-supplied TOML config (`--config` or `local.toml`)
+    User,
+    // locally installed encrypted config package
+    Package,
+    // config package fetched from the update URL or mirrors
+    Remote,
+    // DNS discovery
+    Dns,
+    // node self-registration store
+    Registry,
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Provenance::User => "user",
+            Provenance::Package => "package",
+            Provenance::Remote => "remote",
+            Provenance::Dns => "dns",
+            Provenance::Registry => "registry",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[async_trait]
+pub trait NodeSource: Send + Sync {
+    fn provenance(&self) -> Provenance;
+
+    /// Produces the node list of the source in response to the event.
+    /// Returns `None` if the event does not concern the source or the
+    /// list has not changed, in which case the previous list is retained.
+    async fn poll(&self, event: &Events) -> Result<Option<Vec<Arc<Node>>>>;
+}
+
+/// User TOML config, loaded on startup and reloaded when modified.
+pub struct UserConfigSource {
+    // node list loaded on startup
+    nodes: Mutex<Option<Vec<Arc<Node>>>>,
+}
+
+impl UserConfigSource {
+    pub fn new(nodes: Vec<Arc<Node>>) -> Self {
+        Self {
+            nodes: Mutex::new(Some(nodes)),
+        }
+    }
+}
+
+#[async_trait]
+impl NodeSource for UserConfigSource {
+    fn provenance(&self) -> Provenance {
+        Provenance::User
+    }
+
+    async fn poll(&self, event: &Events) -> Result<Option<Vec<Arc<Node>>>> {
+        match event {
+            Events::Start => Ok(self.nodes.lock().unwrap().take()),
+            Events::Reload => match reload_user_config() {
+                Ok(Some(node_list)) => {
+                    log_success!("Config", "Reloaded {} node(s)", node_list.len());
+                    Ok(Some(node_list))
+                }
+                Ok(None) => Ok(None),
+                Err(err) => {
+                    let err = match err {
+                        Error::Config(msg) => msg,
+                        err => err.to_string(),
+                    };
+                    log_error!("Config", "!!! Failed to reload config: {err}");
+                    log_error!("Config", "!!! Retaining previous configuration");
+                    Ok(None)
+                }
+            },
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Locally installed config package (or the default config).
+pub struct PackageSource;
+
+#[async_trait]
+impl NodeSource for PackageSource {
+    fn provenance(&self) -> Provenance {
+        Provenance::Package
+    }
+
+    async fn poll(&self, event: &Events) -> Result<Option<Vec<Arc<Node>>>> {
+        match event {
+            Events::Start | Events::Update | Events::Reload => Ok(Some(load_config()?)),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Config package fetched from the update sources. Falls back
+/// to the locally installed package if the first update fails.
+pub struct RemoteSource;
+
+#[async_trait]
+impl NodeSource for RemoteSource {
+    fn provenance(&self) -> Provenance {
+        Provenance::Remote
+    }
+
+    async fn poll(&self, event: &Events) -> Result<Option<Vec<Arc<Node>>>> {
+        match event {
+            Events::Start => match update_global_config(false).await {
+                Ok(node_list) => Ok(node_list),
+                // fallback to local config on first update
+                Err(_) => Ok(Some(load_config()?)),
+            },
+            Events::Update | Events::Reload => update_global_config(false).await,
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Nodes resolved from DNS discovery sources.
+pub struct DnsSource {
+    discovery: Discovery,
+}
+
+impl DnsSource {
+    pub fn new(discovery: Discovery) -> Self {
+        Self { discovery }
+    }
+}
+
+#[async_trait]
+impl NodeSource for DnsSource {
+    fn provenance(&self) -> Provenance {
+        Provenance::Dns
+    }
+
+    async fn poll(&self, event: &Events) -> Result<Option<Vec<Arc<Node>>>> {
+        match event {
+            Events::Start | Events::Discover => {
+                if self.discovery.resolve().await? {
+                    Ok(Some(self.discovery.nodes()))
+                } else {
+                    Ok(None)
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Approved node registrations.
+pub struct RegistrySource {
+    registry: Arc<Registry>,
+}
+
+impl RegistrySource {
+    pub fn new(registry: &Arc<Registry>) -> Self {
+        Self {
+            registry: registry.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl NodeSource for RegistrySource {
+    fn provenance(&self) -> Provenance {
+        Provenance::Registry
+    }
+
+    async fn poll(&self, event: &Events) -> Result<Option<Vec<Arc<Node>>>> {
+        match event {
+            Events::Start | Events::Refresh => Ok(Some(self.registry.nodes())),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Node sources in the order of precedence along with
+/// the most recent node list of each source.
+pub struct Composer {
+    sources: Vec<(Box<dyn NodeSource>, Mutex<Vec<Arc<Node>>>)>,
+    // uids of duplicate nodes reported by the last merge
+    duplicates: Mutex<HashSet<u64>>,
+}
+
+impl Composer {
+    pub fn new(sources: Vec<Box<dyn NodeSource>>) -> Self {
+        let sources = sources
+            .into_iter()
+            .map(|source| (source, Mutex::new(vec![])))
+            .collect();
+        Self {
+            sources,
+            duplicates: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Config sources of the resolver: the user config if present,
+    /// otherwise the remote package (with `--auto-update`) or the
+    /// local package, followed by DNS discovery and the registry.
+    pub fn with_args(args: &Args, registry: &Arc<Registry>) -> Self {
+        let config: Box<dyn NodeSource> = if let Some(nodes) = user_config() {
+            Box::new(UserConfigSource::new(nodes))
+        } else if args.auto_update {
+            Box::new(RemoteSource)
+        } else {
+            Box::new(PackageSource)
+        };
+
+        Self::new(vec![
+            config,
+            Box::new(DnsSource::new(Discovery::default())),
+            Box::new(RegistrySource::new(registry)),
+        ])
+    }
+
+    /// Polls all sources with the event. Returns `true` if any of
+    /// the sources produced a node list. Failing sources are logged
+    /// and retain their previous list.
+    pub async fn poll(&self, event: &Events) -> bool {
+        let mut changed = false;
+        for (source, nodes) in self.sources.iter() {
+            match source.poll(event).await {
+                Ok(Some(list)) => {
+                    let provenance = source.provenance();
+                    *nodes.lock().unwrap() = list
+                        .iter()
+                        .map(|node| node.with_provenance(provenance))
+                        .collect();
+                    changed = true;
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    log_error!("Sources", "[{event:?}] {}: {err}", source.provenance());
+                }
+            }
+        }
+        changed
+    }
+
+    /// Indicates if a source other than the registry
+    /// produced a node with the address.
+    pub fn is_configured(&self, address: &str) -> bool {
+        let address = address.trim_end_matches('/');
+        self.sources
+            .iter()
+            .filter(|(source, _)| source.provenance() != Provenance::Registry)
+            .any(|(_, nodes)| {
+                nodes.lock().unwrap().iter().any(|node| {
+                    node.address()
+                        .trim_end_matches('/')
+                        .eq_ignore_ascii_case(address)
+                })
+            })
+    }
+
+    /// Merged node list of all sources, tagged with their provenance.
+    /// Duplicate nodes are logged when they first appear.
+    pub fn nodes(&self) -> Vec<Arc<Node>> {
+        let mut uids = HashSet::new();
+        let mut merged = vec![];
+        let mut reported = self.duplicates.lock().unwrap();
+        let mut duplicates = HashSet::new();
+        for (source, nodes) in self.sources.iter() {
+            let provenance = source.provenance();
+            for node in nodes.lock().unwrap().iter() {
+                if uids.insert(node.uid()) {
+                    merged.push(node.clone());
+                } else {
+                    if !reported.contains(&node.uid()) {
+                        log_warn!("Sources", "Ignoring duplicate {provenance} node {node}");
+                    }
+                    duplicates.insert(node.uid());
+                }
+            }
+        }
+        *reported = duplicates;
+        merged
+    }
+}
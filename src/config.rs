@@ -1,83 +1,292 @@
+use std::net::{IpAddr, SocketAddr};
 use std::sync::LazyLock;
+use std::time::SystemTime;
 
 use crate::imports::*;
+use crate::include::{self, ConfigFile};
 use chrono::prelude::*;
+use toml::Spanned;
 
-const VERSION: u64 = 3;
+/// Config package schema version (see [`migrate`]).
+pub const VERSION: u64 = 3;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
+    // additional config files (see [`include`])
+    #[serde(skip_serializing)]
+    include: Option<Vec<String>>,
     #[serde(rename = "transport")]
     transports: Option<TransportDictionary>,
     #[serde(rename = "group")]
-    groups: Option<Vec<Group>>,
+    #[schemars(with = "Option<Vec<Group>>")]
+    groups: Option<Vec<Spanned<Group>>>,
     #[serde(rename = "node")]
-    nodes: Option<Vec<NodeConfig>>,
+    #[schemars(with = "Option<Vec<NodeConfig>>")]
+    nodes: Option<Vec<Spanned<NodeConfig>>>,
+}
+
+/// Config entries merged from one or more config files,
+/// each entry annotated with its `<file>:<line>` origin.
+struct Merged {
+    transports: TransportDictionary,
+    groups: Vec<(String, Group)>,
+    nodes: Vec<(String, NodeConfig)>,
 }
 
 impl Config {
+    /// Parses a single config document (such as the packaged config).
     pub fn try_parse(toml: &str) -> Result<Vec<Arc<Node>>> {
-        let config = toml::from_str::<Config>(toml)?;
+        let file = ConfigFile::new("config", toml);
+        Self::build(Self::merge(std::slice::from_ref(&file), false)?)
+    }
 
-        let mut nodes: Vec<Arc<Node>> = config
-            .nodes
-            .map(|nodes| {
-                nodes
-                    .into_iter()
-                    .filter_map(|node| {
-                        node.enable
-                            .unwrap_or(true)
-                            .then_some(node.into())
-                            .map(Arc::new)
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
+    /// Loads a config file along with the files it includes
+    /// and the files located in the adjacent `conf.d` directory.
+    pub fn load(path: &Path) -> Result<Vec<Arc<Node>>> {
+        Self::build(Self::merge(&include::load(path)?, true)?)
+    }
+
+    /// Loads and validates a config file, producing a single
+    /// config document with includes merged (used for packaging).
+    pub fn load_merged(path: &Path) -> Result<String> {
+        let files = include::load(path)?;
+        let merged = Self::merge(&files, true)?;
+
+        let toml = if let [file] = files.as_slice() {
+            file.toml.clone()
+        } else {
+            log::info(format!("Merging {} config files", files.len()))?;
+            let config = Config {
+                include: None,
+                transports: Some(merged.transports.clone()),
+                groups: Some(
+                    merged
+                        .groups
+                        .iter()
+                        .map(|(_, group)| Spanned::new(0..0, group.clone()))
+                        .collect(),
+                ),
+                nodes: Some(
+                    merged
+                        .nodes
+                        .iter()
+                        .map(|(_, node)| Spanned::new(0..0, node.clone()))
+                        .collect(),
+                ),
+            };
+            toml::to_string(&config)
+                .map_err(|err| Error::config(format!("Unable to serialize config: {err}")))?
+        };
+
+        Self::build(merged)?;
+        Ok(toml)
+    }
+
+    fn merge(files: &[ConfigFile], includes: bool) -> Result<Merged> {
+        let mut transports = TransportDictionary::default();
+        let mut transport_origins = HashMap::<String, &Path>::new();
+        let mut groups = Vec::new();
+        let mut nodes = Vec::new();
+
+        for file in files {
+            let unknown_keys = schema::unknown_keys(&file.toml);
+            if ConfigSettings::strict() && !unknown_keys.is_empty() {
+                return Err(Error::config(format!(
+                    "{}: {}",
+                    file.path.display(),
+                    unknown_keys.join(", ")
+                )));
+            }
+            for issue in unknown_keys.iter() {
+                log_warn!("Config", "{}: {issue}", file.path.display());
+            }
 
-        let groups: Vec<Group> = config
-            .groups
-            .map(|groups| {
-                groups
+            let config = toml::from_str::<Config>(&file.toml)
+                .map_err(|err| Error::config(format!("{}: {err}", file.path.display())))?;
+
+            if !includes && config.include.is_some_and(|include| !include.is_empty()) {
+                return Err(Error::config(format!(
+                    "{}: includes are supported only in config files",
+                    file.path.display()
+                )));
+            }
+
+            for (name, transport) in config.transports.unwrap_or_default().into_iter() {
+                transport.validate().map_err(|err| {
+                    err.context(format!("{}: transport `{name}`", file.path.display()))
+                })?;
+                if let Some(previous) = transport_origins.insert(name.clone(), &file.path) {
+                    return Err(Error::config(format!(
+                        "{}: duplicate transport `{name}` (previously defined in {})",
+                        file.path.display(),
+                        previous.display()
+                    )));
+                }
+                transports.insert(name, transport);
+            }
+
+            groups.extend(
+                config
+                    .groups
                     .into_iter()
-                    .filter_map(|cluster| cluster.enable.unwrap_or(true).then_some(cluster))
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
+                    .flatten()
+                    .map(|group| (file.origin(group.span()), group.into_inner())),
+            );
+            nodes.extend(
+                config
+                    .nodes
+                    .into_iter()
+                    .flatten()
+                    .map(|node| (file.origin(node.span()), node.into_inner())),
+            );
+        }
 
-        let mut unique_groups = HashSet::new();
-        for group in groups.iter() {
-            if !unique_groups.insert(group.fqdn.clone()) {
-                return Err(Error::config(format!("Duplicate group: {}", group.fqdn)));
+        Ok(Merged {
+            transports,
+            groups,
+            nodes,
+        })
+    }
+
+    fn build(merged: Merged) -> Result<Vec<Arc<Node>>> {
+        let Merged {
+            transports: transport_dictionary,
+            groups,
+            nodes: node_configs,
+        } = merged;
+
+        for (origin, node) in node_configs.iter() {
+            if node.id.as_deref().is_some_and(|id| id.trim().is_empty()) {
+                return Err(Error::config(format!(
+                    "{origin}: Node `{}`: empty node id",
+                    node.fqdn
+                )));
+            }
+            if let Some(auth) = &node.auth {
+                auth.validate()
+                    .map_err(|err| err.context(format!("{origin}: Node `{}`", node.fqdn)))?;
             }
+            node.limits
+                .validate()
+                .map_err(|err| err.context(format!("{origin}: Node `{}`", node.fqdn)))?;
         }
 
-        let transport_dictionary = &config.transports.unwrap_or_default();
+        for (origin, group) in groups.iter() {
+            if let Some(auth) = &group.auth {
+                auth.validate()
+                    .map_err(|err| err.context(format!("{origin}: Group `{}`", group.fqdn)))?;
+            }
+            group
+                .limits
+                .validate()
+                .map_err(|err| err.context(format!("{origin}: Group `{}`", group.fqdn)))?;
+        }
 
-        for group in groups.iter() {
+        let mut nodes: Vec<(String, Arc<Node>)> = node_configs
+            .into_iter()
+            .filter_map(|(origin, node)| {
+                node.enable
+                    .unwrap_or(true)
+                    .then(|| (origin, Arc::new(node.into())))
+            })
+            .collect::<Vec<_>>();
+
+        let groups: Vec<(String, Group)> = groups
+            .into_iter()
+            .filter(|(_, group)| group.enable.unwrap_or(true))
+            .collect::<Vec<_>>();
+
+        let mut unique_groups = HashMap::new();
+        for (origin, group) in groups.iter() {
+            if let Some(previous) = unique_groups.insert(group.fqdn.clone(), origin) {
+                return Err(Error::config(format!(
+                    "{origin}: duplicate group `{}` (previously defined at {previous})",
+                    group.fqdn
+                )));
+            }
+        }
+
+        for (origin, group) in groups.iter() {
             if !group.fqdn.contains('*') {
-                log_error!("Config", "Invalid group FQDN: {}", group.fqdn);
+                log_error!("Config", "{origin}: Invalid group FQDN: {}", group.fqdn);
             } else {
+                group
+                    .validate_members()
+                    .map_err(|err| err.context(format!("{origin}: Group `{}`", group.fqdn)))?;
+
                 let Group {
                     fqdn,
                     transports,
                     services,
                     network,
+                    auth,
+                    limits,
+                    tags,
                     ..
                 } = group;
 
+                let credentials = auth.clone().map(Arc::new);
+
                 for service in services.iter() {
                     for (network_id, ids) in network.iter() {
+                        let ids = group
+                            .member_ids(ids)
+                            .map_err(|err| err.context(format!("{origin}: Group `{fqdn}`")))?;
                         for transport in transports.iter() {
-                            for id in ids {
+                            for id in ids.iter() {
+                                let member = group.member(id).cloned().unwrap_or_default();
+                                if !member.enable.unwrap_or(true) {
+                                    continue;
+                                }
+
                                 if let Some(transport) = transport_dictionary.get(transport) {
                                     let fqdn = fqdn.replace('*', &id.to_lowercase());
-                                    let address =
-                                        transport.make_address(&fqdn, service, network_id);
-                                    let node =
-                                        Node::new(service, *network_id, transport, fqdn, address);
-                                    nodes.push(node);
+                                    let address = match &member.address {
+                                        Some(template) => Transport {
+                                            template: template.clone(),
+                                            ..transport.clone()
+                                        }
+                                        .make_address(&fqdn, service, network_id),
+                                        None => transport.make_address(&fqdn, service, network_id),
+                                    }
+                                    .map_err(|err| {
+                                        err.context(format!(
+                                            "{origin}: Group `{}` member `{id}`",
+                                            group.fqdn
+                                        ))
+                                    })?;
+                                    let limits = NodeLimits {
+                                        weight: member.weight.or(limits.weight),
+                                        ..*limits
+                                    };
+                                    let tags = tags
+                                        .iter()
+                                        .flatten()
+                                        .chain(member.tags.iter().flatten())
+                                        .cloned()
+                                        .collect();
+                                    let options = NodeOptions {
+                                        id: member.id.clone(),
+                                        group: Some(group.fqdn.clone()),
+                                        credentials: credentials.clone(),
+                                        limits,
+                                        tags,
+                                    };
+                                    let node = Node::new(
+                                        service,
+                                        *network_id,
+                                        transport,
+                                        fqdn,
+                                        address,
+                                        options,
+                                    );
+                                    nodes.push((origin.clone(), node));
                                 } else {
-                                    log_error!("Config", "Unknown transport: {}", transport);
+                                    log_error!(
+                                        "Config",
+                                        "{origin}: Unknown transport: {}",
+                                        transport
+                                    );
                                 }
                             }
                         }
@@ -86,7 +295,28 @@ impl Config {
             }
         }
 
-        Ok(nodes)
+        nodes.retain(|(origin, node)| {
+            let supported = node.transport_kind().is_supported();
+            if !supported {
+                log_warn!(
+                    "Config",
+                    "{origin}: Ignoring node `{node}` (unsupported transport `{}`)",
+                    node.transport_kind()
+                );
+            }
+            supported
+        });
+
+        let mut unique_nodes = HashMap::new();
+        for (origin, node) in nodes.iter() {
+            if let Some(previous) = unique_nodes.insert(node.uid(), origin) {
+                return Err(Error::config(format!(
+                    "{origin}: duplicate node `{node}` (uid collides with entry at {previous})"
+                )));
+            }
+        }
+
+        Ok(nodes.into_iter().map(|(_, node)| node).collect())
     }
 }
 
@@ -109,14 +339,27 @@ mod fs {
     }
 }
 
-static USER_CONFIG: LazyLock<Mutex<Option<Vec<Arc<Node>>>>> = LazyLock::new(|| Mutex::new(None));
+struct UserConfig {
+    path: PathBuf,
+    // modification times of the config file, the files it
+    // includes and the adjacent `conf.d` directory
+    modified: Vec<(PathBuf, Option<SystemTime>)>,
+    nodes: Vec<Arc<Node>>,
+}
+
+static USER_CONFIG: LazyLock<Mutex<Option<UserConfig>>> = LazyLock::new(|| Mutex::new(None));
 
 pub fn user_config() -> Option<Vec<Arc<Node>>> {
-    USER_CONFIG.lock().unwrap().clone()
+    USER_CONFIG
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|user_config| user_config.nodes.clone())
 }
 
-pub fn init(user_config: &Option<PathBuf>) -> Result<()> {
-    Settings::load();
+pub fn init(args: &Args) -> Result<()> {
+    Settings::load(args.settings.as_deref(), &args.set)?;
+    let user_config = &args.user_config;
 
     let global_config_folder = global_config_folder();
     if !global_config_folder.exists() {
@@ -130,11 +373,7 @@ pub fn init(user_config: &Option<PathBuf>) -> Result<()> {
             "Using local config override: `{}`",
             local_config_override.display()
         );
-        let toml = fs::read_to_string(local_config_override)?;
-        USER_CONFIG
-            .lock()
-            .unwrap()
-            .replace(Config::try_parse(toml.as_str())?);
+        load_user_config(local_config_override)?;
     } else if let Some(user_config) = user_config {
         // let config_path = Path::new(config);
         if !user_config.exists() {
@@ -143,17 +382,100 @@ pub fn init(user_config: &Option<PathBuf>) -> Result<()> {
                 user_config.display()
             )))?;
         } else {
-            let toml = fs::read_to_string(user_config)?;
-            USER_CONFIG
-                .lock()
-                .unwrap()
-                .replace(Config::try_parse(toml.as_str())?);
+            load_user_config(user_config.clone())?;
         }
     }
 
     Ok(())
 }
 
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+// paths whose modification triggers a user config reload
+fn watched(path: &Path, files: &[ConfigFile]) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let conf_d = path.parent().map(|folder| folder.join("conf.d"));
+    files
+        .iter()
+        .map(|file| file.path.clone())
+        .chain(conf_d)
+        .map(|path| {
+            let modified = modified(&path);
+            (path, modified)
+        })
+        .collect()
+}
+
+fn load_user_config(path: PathBuf) -> Result<Vec<Arc<Node>>> {
+    let files = include::load(&path)?;
+    let modified = watched(&path, &files);
+    let nodes = Config::build(Config::merge(&files, true)?)?;
+    warn_plaintext_credentials(&nodes, &path);
+    USER_CONFIG.lock().unwrap().replace(UserConfig {
+        path,
+        modified,
+        nodes: nodes.clone(),
+    });
+    Ok(nodes)
+}
+
+/// Indicates if the user config file, any of the files it includes
+/// or the `conf.d` directory has been modified since it was last loaded.
+pub fn user_config_changed() -> bool {
+    USER_CONFIG
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|user_config| {
+            user_config
+                .modified
+                .iter()
+                .any(|(path, time)| modified(path) != *time)
+        })
+}
+
+/// Re-parses the user config file. Returns `Ok(None)` if the
+/// resolver is not running with a user config. If the config
+/// fails to parse, the previously loaded config is retained.
+pub fn reload_user_config() -> Result<Option<Vec<Arc<Node>>>> {
+    let path = {
+        let mut user_config = USER_CONFIG.lock().unwrap();
+        let Some(user_config) = user_config.as_mut() else {
+            return Ok(None);
+        };
+        // register the modification time even if the config
+        // fails to parse to avoid repeated reload attempts
+        for (path, time) in user_config.modified.iter_mut() {
+            *time = modified(path);
+        }
+        user_config.path.clone()
+    };
+
+    log_info!("Config", "Reloading `{}`", path.display());
+    load_user_config(path).map(Some)
+}
+
+fn warn_plaintext_credentials(nodes: &[Arc<Node>], path: &Path) {
+    let count = nodes
+        .iter()
+        .filter(|node| node.credentials().is_some())
+        .count();
+    if count > 0 {
+        log_warn!(
+            "Config",
+            "{count} node(s) with credentials loaded from plain-text config `{}`",
+            path.display()
+        );
+        log_warn!(
+            "Config",
+            "Node credentials should be distributed only within the encrypted package"
+        );
+    }
+}
+
 pub fn global_config_folder() -> PathBuf {
     dirs::home_dir()
         .expect("Could not find home folder")
@@ -186,8 +508,20 @@ fn key64_file() -> String {
     ".key64".to_string()
 }
 
-fn global_config_file() -> String {
-    format!("resolver.{VERSION}.bin")
+pub fn global_config_file() -> String {
+    package_file(VERSION)
+}
+
+pub fn global_signature_file() -> String {
+    signature_file(VERSION)
+}
+
+pub fn package_file(version: u64) -> String {
+    format!("resolver.{version}.bin")
+}
+
+fn signature_file(version: u64) -> String {
+    format!("resolver.{version}.sig")
 }
 
 fn local_config_file() -> String {
@@ -229,9 +563,8 @@ pub fn locate_local_config() -> Option<PathBuf> {
 
 pub fn test_config() -> Result<Vec<Arc<Node>>> {
     let local_config = locate_local_config().ok_or(Error::LocalConfigNotFound)?;
-    let toml = fs::read_to_string(&local_config)?;
     // let local = include_str!("../Resolver.toml");
-    Config::try_parse(toml.as_str())
+    Config::load(&local_config)
 }
 
 pub fn load_config() -> Result<Vec<Arc<Node>>> {
@@ -246,10 +579,24 @@ pub fn load_global_config() -> Result<Vec<Arc<Node>>> {
     if !global_config_folder.exists() {
         fs::create_dir_all(&global_config_folder)?;
     }
-    let key = load_key()?;
-    let data = fs::read(global_config_folder.join(global_config_file()))?;
-    let toml = chacha20poly1305::decrypt_slice(&data, &key)?;
-    Config::try_parse(toml.as_str()?)
+    load_package(&global_config_folder)
+}
+
+// loads, verifies and decrypts the config package located in the folder
+fn load_package(folder: &Path) -> Result<Vec<Arc<Node>>> {
+    let data = fs::read(folder.join(global_config_file()))?;
+    let signature = read_signature(folder.join(global_signature_file()))?;
+    signing::verify(&data, signature.as_deref())?;
+    let toml = package::decrypt(&data)?;
+    Config::try_parse(toml.as_str())
+}
+
+fn read_signature(path: PathBuf) -> Result<Option<Vec<u8>>> {
+    if path.exists() {
+        Ok(Some(fs::read(path)?))
+    } else {
+        Ok(None)
+    }
 }
 
 pub fn load_default_config() -> Result<Vec<Arc<Node>>> {
@@ -258,52 +605,92 @@ pub fn load_default_config() -> Result<Vec<Arc<Node>>> {
 
     if local_config.exists() {
         log_info!("Config", "Using local config: `{}`", local_config.display());
-        let toml = fs::read_to_string(local_config)?;
-        Config::try_parse(toml.as_str())
+        Config::load(&local_config)
     } else {
         let local_config = local_config_folder.join(global_config_file());
         log_info!("Config", "Using local config: `{}`", local_config.display());
-        let key = load_key()?;
-        let data = fs::read(local_config)?;
-        let toml = chacha20poly1305::decrypt_slice(&data, &key)?;
-        Config::try_parse(toml.as_str()?)
+        load_package(&local_config_folder)
     }
 }
 
-pub async fn update_global_config() -> Result<Option<Vec<Arc<Node>>>> {
-    static HASH: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+/// Fetches the global config package and applies it if it differs from
+/// the previously fetched package. Changes are reviewed against the
+/// currently installed config and refused if they remove all nodes.
+/// If the config is pinned (see [`history`]), the pinned package is
+/// applied from the history instead. In `dry_run` mode the changes
+/// are only displayed.
+pub async fn update_global_config(dry_run: bool) -> Result<Option<Vec<Arc<Node>>>> {
+    static HASH: Mutex<Option<String>> = Mutex::new(None);
 
     log_info!("Config", "Updating resolver config");
 
-    let url = format!("{}{}", Updates::url(), global_config_file());
-    let data = reqwest::get(&url).await?.bytes().await?.to_vec();
-
-    if data.len() < 24 {
-        println!("Error fetching: {url}");
-        return Err(Error::custom(format!(
-            "Update: invalid data length: {}",
-            data.len()
-        )));
-    }
+    let package = match history::pinned()? {
+        Some(hash) => {
+            log_warn!("Config", "Config is pinned to package `{hash}`");
+            history::package(&hash)?
+        }
+        None => {
+            let package = sources::fetch_package().await?;
+            log_info!(
+                "Config",
+                "Using update source `{}` (package `{}`)",
+                package.source,
+                package.hash
+            );
+            package
+        }
+    };
 
-    let hash = sha256(data.as_slice());
     let mut previous = HASH.lock().unwrap();
-    if previous.as_deref() == Some(hash.as_slice()) {
+    if previous.as_deref() == Some(package.hash.as_str()) {
         log_warn!("Config", "No changes detected");
+        if !dry_run {
+            sources::set_active(&package);
+        }
         Ok(None)
     } else {
         log_warn!("Config", "Changes detected");
-        *previous = Some(hash.as_slice().to_vec());
-        let key = load_key()?;
-        let toml = chacha20poly1305::decrypt_slice(&data, &key)?;
-        let config = Config::try_parse(toml.as_str()?)?;
-        let global_config_file = global_config_folder().join(global_config_file());
-        fs::write(&global_config_file, data)?;
-        log_info!("Config", "Updating: `{}`", global_config_file.display());
+        if let Some(publisher) = package.publisher.as_ref() {
+            log_success!("Config", "Package signed by `{publisher}`");
+        }
+        let toml = package::decrypt(&package.data)?;
+        let config = Config::try_parse(toml.as_str())?;
+
+        let installed = load_config().unwrap_or_default();
+        let diff = NodeDiff::new(&installed, &config);
+        diff.render();
+        diff.validate()?;
+
+        if dry_run {
+            log_info!("Config", "Dry run, no changes applied");
+            return Ok(None);
+        }
+
+        sources::set_active(&package);
+        *previous = Some(package.hash.clone());
+        install_package(&package)?;
+        if let Err(err) = history::record(&package) {
+            log_error!("Config", "Unable to record config history: {err}");
+        }
         Ok(Some(config))
     }
 }
 
+/// Writes the package (and its signature) as the global config.
+pub fn install_package(package: &sources::Package) -> Result<()> {
+    let global_config_file = global_config_folder().join(global_config_file());
+    let global_signature_file = global_config_folder().join(global_signature_file());
+    fs::write(&global_config_file, &package.data)?;
+    if let Some(signature) = package.signature.as_ref() {
+        fs::write(&global_signature_file, signature)?;
+    } else if global_signature_file.exists() {
+        std::fs::remove_file(&global_signature_file)
+            .map_err(|err| Error::file(&global_signature_file, err))?;
+    }
+    log_info!("Config", "Updating: `{}`", global_config_file.display());
+    Ok(())
+}
+
 pub fn generate_key() -> Result<()> {
     let key_path = global_config_folder().join(key_file());
     let key64_path = global_config_folder().join(key64_file());
@@ -369,8 +756,6 @@ fn prefix(key: &Secret) -> String {
 }
 
 pub fn pack() -> Result<()> {
-    let key = get_key()?;
-    log::info(format!("Packing key prefix `{}`", prefix(&key)))?;
     let local_config_folder = local_config_folder().ok_or(Error::LocalConfigNotFound)?;
     let local_config_file = local_config_folder.join(local_config_file());
     let local_data_file = local_config_folder.join(global_config_file());
@@ -379,18 +764,38 @@ pub fn pack() -> Result<()> {
         local_config_file.display(),
         local_data_file.display()
     ))?;
-    let toml = fs::read_to_string(local_config_file)?;
-    Config::try_parse(toml.as_str())?;
-    let data = chacha20poly1305::encrypt_slice(toml.as_bytes(), &key)?;
-    fs::write(local_data_file, &data)?;
-    log::success(format!("Package size {}", data.len()))?;
+    let toml = Config::load_merged(&local_config_file)?;
+    write_package(&local_config_folder, VERSION, &toml)?;
     outro("Have a great day!")?;
     Ok(())
 }
 
+/// Encrypts the config for package recipients (or with the shared
+/// key if there are no recipients), signs it and writes the package
+/// `resolver.<version>.bin` along with its signature to the folder.
+pub fn write_package(folder: &Path, version: u64, toml: &str) -> Result<()> {
+    let recipients = package::load_recipients()?;
+    let data = if recipients.is_empty() {
+        let key = get_key()?;
+        log::info(format!("Packing key prefix `{}`", prefix(&key)))?;
+        AsRef::<[u8]>::as_ref(&chacha20poly1305::encrypt_slice(toml.as_bytes(), &key)?).to_vec()
+    } else {
+        log::info(format!("Packing for {} recipients", recipients.len()))?;
+        package::encrypt(toml, &recipients)?
+    };
+    let publisher_key = signing::get_publisher_key()?;
+    let signature = signing::sign(&publisher_key, &data);
+    fs::write(folder.join(package_file(version)), &data)?;
+    fs::write(folder.join(signature_file(version)), signature)?;
+    log::success(format!("Package size {}", data.len()))?;
+    log::success(format!(
+        "Signed by `{}`",
+        publisher_key.verifying_key().to_bytes().to_vec().to_hex()
+    ))?;
+    Ok(())
+}
+
 pub fn unpack() -> Result<()> {
-    let key = get_key()?;
-    log::info(format!("Unpacking key prefix `{}`", prefix(&key)))?;
     let local_config_folder = local_config_folder().ok_or(Error::LocalConfigNotFound)?;
     let local_data_file = local_config_folder.join(global_config_file());
     let local_config_file = if local_config_folder.join(local_config_file()).exists() {
@@ -404,8 +809,14 @@ pub fn unpack() -> Result<()> {
         local_config_folder.join(local_config_file())
     };
     let data = fs::read(local_data_file)?;
-    let toml = chacha20poly1305::decrypt_slice(&data, &key)?;
-    Config::try_parse(toml.as_str()?)?;
+    if package::is_multi_recipient(&data) {
+        log::info("Unpacking multi-recipient package")?;
+    } else {
+        let key = get_key()?;
+        log::info(format!("Unpacking key prefix `{}`", prefix(&key)))?;
+    }
+    let toml = package::decrypt(&data)?;
+    Config::try_parse(toml.as_str())?;
     fs::write(&local_config_file, toml)?;
     log::success(format!(
         "Unpacked TOML at: `{}`",
@@ -415,36 +826,158 @@ pub fn unpack() -> Result<()> {
     Ok(())
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Settings {
     updates: Updates,
     limits: Limits,
     sync: SyncSettings,
+    metrics: MetricsSettings,
     ttl: TtlSettings,
     http: HttpSettings,
+    #[serde(default)]
+    params: ParamsSettings,
+    #[serde(default)]
+    config: ConfigSettings,
+    #[serde(default)]
+    discovery: DiscoverySettings,
+    #[serde(default)]
+    registry: RegistrySettings,
 }
 
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
 impl Settings {
-    pub fn load() {
-        let _ = Settings::get();
-        // validate ttl settings
-        TtlSettings::ttl();
+    /// Loads and validates layered settings (see [`settings`]).
+    /// Must be called before the first use of [`Settings::get`].
+    pub fn load(file: Option<&Path>, overrides: &[String]) -> Result<()> {
+        let settings = settings::init(file, overrides)?;
+        SETTINGS
+            .set(settings)
+            .map_err(|_| Error::custom("Settings are already initialized"))
     }
 
     pub fn get() -> &'static Self {
-        static SETTINGS: OnceLock<Settings> = OnceLock::new();
-        SETTINGS.get_or_init(|| {
-            let toml = include_str!("../Resolver.toml");
-            toml::from_str::<Settings>(toml).unwrap()
-        })
+        SETTINGS.get_or_init(|| toml::from_str::<Settings>(settings::DEFAULTS).unwrap())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let Settings {
+            updates,
+            limits,
+            sync,
+            metrics,
+            ttl,
+            http,
+            params: _,
+            config: _,
+            discovery,
+            registry,
+        } = self;
+
+        for source in std::iter::once(&UpdateSource::new(&updates.url))
+            .chain(updates.mirrors.iter().flatten())
+        {
+            if !["https://", "http://", "file://"]
+                .iter()
+                .any(|scheme| source.url.starts_with(scheme))
+            {
+                return Err(invalid("updates.url", "unsupported update source"));
+            }
+            positive("updates.mirror.timeout-sec", source.timeout_sec)?;
+        }
+        positive("updates.duration-hrs", Some(updates.duration))?;
+        positive("updates.timeout-sec", updates.timeout_sec)?;
+        if updates
+            .max_removal_pct
+            .is_some_and(|pct| !(0.0..=100.0).contains(&pct))
+        {
+            return Err(invalid("updates.max-removal-pct", "must be within 0..100"));
+        }
+        if updates.history == Some(0) {
+            return Err(invalid("updates.history", "must be positive"));
+        }
+        for key in updates.trusted_keys.iter().flatten() {
+            if <[u8; 32]>::from_hex(key.trim()).is_err() {
+                return Err(invalid("updates.trusted-keys", "invalid public key"));
+            }
+        }
+
+        if limits.fd == 0 {
+            return Err(invalid("limits.fd", "must be positive"));
+        }
+
+        positive("sync.poll-sec", Some(sync.poll_sec))?;
+        positive("sync.ping-sec", Some(sync.ping_sec))?;
+        positive("sync.reconcile-sec", sync.reconcile_sec)?;
+
+        positive("metrics.poll-sec", Some(metrics.poll_sec))?;
+        if metrics.weight < 0.0 {
+            return Err(invalid("metrics.weight", "must not be negative"));
+        }
+
+        if ttl.period_sec.is_none() && ttl.period_hrs.is_none() {
+            return Err(invalid("ttl.period-hrs", "TTL period not set"));
+        }
+        positive("ttl.period-sec", ttl.period_sec)?;
+        positive("ttl.period-hrs", ttl.period_hrs)?;
+        if !(0.0..=1.0).contains(&ttl.noise) {
+            return Err(invalid("ttl.noise", "must be within 0..1"));
+        }
+
+        positive("discovery.interval-sec", discovery.interval_sec)?;
+        DiscoverySettings::parse_nameservers(discovery.nameservers.as_deref().unwrap_or_default())?;
+        for source in discovery.sources.iter().flatten() {
+            source.validate().map_err(|err| {
+                invalid(&format!("discovery.source `{source}`"), &err.to_string())
+            })?;
+        }
+
+        if registry.max_per_operator == Some(0) {
+            return Err(invalid("registry.max-per-operator", "must be positive"));
+        }
+
+        if http.status.sessions == Some(0) {
+            return Err(invalid("http.status.sessions", "must be positive"));
+        }
+        positive("http.status.ttl-hrs", http.status.ttl_hrs)?;
+
+        Ok(())
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+fn invalid(key: &str, reason: &str) -> Error {
+    Error::config(format!("Invalid setting `{key}`: {reason}"))
+}
+
+fn positive(key: &str, value: Option<f64>) -> Result<()> {
+    match value {
+        Some(value) if !value.is_finite() || value <= 0.0 => {
+            Err(invalid(key, "must be a positive number"))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Updates {
     pub url: String,
     #[serde(rename = "duration-hrs")]
     pub duration: f64,
+    #[serde(rename = "max-removal-pct")]
+    pub max_removal_pct: Option<f64>,
+    #[serde(rename = "trusted-keys")]
+    pub trusted_keys: Option<Vec<String>>,
+    // accept unsigned packages if no trusted keys are configured
+    #[serde(rename = "allow-unsigned")]
+    pub allow_unsigned: Option<bool>,
+    #[serde(rename = "timeout-sec")]
+    pub timeout_sec: Option<f64>,
+    #[serde(rename = "cross-check")]
+    pub cross_check: Option<bool>,
+    #[serde(rename = "mirror")]
+    pub mirrors: Option<Vec<UpdateSource>>,
+    // number of applied packages retained in the history
+    pub history: Option<usize>,
 }
 
 impl Updates {
@@ -452,13 +985,54 @@ impl Updates {
         Settings::get().updates.url.as_str()
     }
 
+    /// Ordered list of update sources: the primary `url` followed by mirrors.
+    pub fn sources() -> Vec<UpdateSource> {
+        std::iter::once(UpdateSource::new(Updates::url()))
+            .chain(Settings::get().updates.mirrors.iter().flatten().cloned())
+            .collect()
+    }
+
+    /// Default fetch timeout of update sources.
+    pub fn timeout() -> Duration {
+        Duration::from_secs_f64(Settings::get().updates.timeout_sec.unwrap_or(10.0))
+    }
+
+    /// Refuse updates unless all reachable sources serve the same package.
+    pub fn cross_check() -> bool {
+        Settings::get().updates.cross_check.unwrap_or(false)
+    }
+
     pub fn duration() -> Duration {
         let seconds = Settings::get().updates.duration * 60.0 * 60.0;
         Duration::from_secs_f64(seconds)
     }
+
+    /// Hex-encoded Ed25519 public keys of trusted package publishers.
+    pub fn trusted_keys() -> &'static [String] {
+        Settings::get()
+            .updates
+            .trusted_keys
+            .as_deref()
+            .unwrap_or_default()
+    }
+
+    /// Accept unsigned packages while no trusted keys are configured.
+    pub fn allow_unsigned() -> bool {
+        Settings::get().updates.allow_unsigned.unwrap_or(true)
+    }
+
+    /// Number of applied packages retained for rollback.
+    pub fn history() -> usize {
+        Settings::get().updates.history.unwrap_or(10)
+    }
+
+    /// Percentage of removed nodes above which updates are flagged.
+    pub fn max_removal_pct() -> f64 {
+        Settings::get().updates.max_removal_pct.unwrap_or(25.0)
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Limits {
     pub fd: u64,
 }
@@ -469,14 +1043,20 @@ impl Limits {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct SyncSettings {
     pub poll_sec: f64,
     pub ping_sec: f64,
+    pub reconcile_sec: Option<f64>,
 }
 
 impl SyncSettings {
+    /// Maximum time to wait for new connections to come online
+    /// during config updates before they become eligible for election.
+    pub fn reconcile() -> Duration {
+        Duration::from_secs_f64(Settings::get().sync.reconcile_sec.unwrap_or(10.0))
+    }
     pub fn poll() -> Duration {
         Duration::from_secs_f64(Settings::get().sync.poll_sec)
     }
@@ -485,7 +1065,29 @@ impl SyncSettings {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct MetricsSettings {
+    pub enable: bool,
+    pub poll_sec: f64,
+    pub weight: f64,
+}
+
+impl MetricsSettings {
+    /// Enables polling of node process metrics used in node scoring.
+    pub fn enable() -> bool {
+        Settings::get().metrics.enable
+    }
+    pub fn poll() -> Duration {
+        Duration::from_secs_f64(Settings::get().metrics.poll_sec)
+    }
+    /// Weight of the resource pressure relative to node capacity.
+    pub fn weight() -> f64 {
+        Settings::get().metrics.weight
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct TtlSettings {
     pub enable: bool,
@@ -514,13 +1116,126 @@ impl TtlSettings {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct ParamsSettings {
+    #[schemars(with = "Option<Vec<String>>")]
+    pub networks: Option<Vec<NetworkId>>,
+    pub transports: Option<Vec<TransportKind>>,
+}
+
+impl ParamsSettings {
+    /// Networks and transports served by the resolver are derived
+    /// from the node configuration unless restricted in settings.
+    /// Nodes of unsupported transports are never accepted.
+    pub fn accepts(node: &Node) -> bool {
+        if !node.transport_kind().is_supported() {
+            return false;
+        }
+        let params = &Settings::get().params;
+        if let Some(networks) = &params.networks {
+            if !networks.contains(&node.network) {
+                return false;
+            }
+        }
+        if let Some(transports) = &params.transports {
+            if !transports.contains(&node.transport_kind()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConfigSettings {
+    pub strict: Option<bool>,
+}
+
+impl ConfigSettings {
+    /// Reject config files containing unknown keys.
+    pub fn strict() -> bool {
+        Settings::get().config.strict.unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct DiscoverySettings {
+    pub interval_sec: Option<f64>,
+    // `<ip>[:<port>]` of DNS servers (default: system configuration)
+    pub nameservers: Option<Vec<String>>,
+    #[serde(rename = "source")]
+    pub sources: Option<Vec<DiscoverySource>>,
+}
+
+impl DiscoverySettings {
+    pub fn sources() -> Vec<DiscoverySource> {
+        Settings::get()
+            .discovery
+            .sources
+            .clone()
+            .unwrap_or_default()
+    }
+
+    pub fn interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().discovery.interval_sec.unwrap_or(300.0))
+    }
+
+    pub fn nameservers() -> Result<Vec<SocketAddr>> {
+        Self::parse_nameservers(
+            Settings::get()
+                .discovery
+                .nameservers
+                .as_deref()
+                .unwrap_or_default(),
+        )
+    }
+
+    fn parse_nameservers(nameservers: &[String]) -> Result<Vec<SocketAddr>> {
+        nameservers
+            .iter()
+            .map(|nameserver| {
+                nameserver
+                    .parse::<SocketAddr>()
+                    .or_else(|_| {
+                        nameserver
+                            .parse::<IpAddr>()
+                            .map(|ip| SocketAddr::new(ip, 53))
+                    })
+                    .map_err(|_| invalid("discovery.nameservers", "invalid address"))
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct RegistrySettings {
+    pub enable: Option<bool>,
+    pub max_per_operator: Option<usize>,
+}
+
+impl RegistrySettings {
+    /// Accept node registrations via `POST /register`.
+    pub fn enable() -> bool {
+        Settings::get().registry.enable.unwrap_or(false)
+    }
+
+    /// Maximum number of registrations per operator.
+    pub fn max_per_operator() -> usize {
+        Settings::get().registry.max_per_operator.unwrap_or(16)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct HttpSettings {
     pub status: HttpStatus,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct HttpStatus {
     pub sessions: Option<usize>,
@@ -541,3 +1256,30 @@ impl HttpStatus {
         Duration::from_secs_f64(ttl_sec)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_folder() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("data")
+    }
+
+    // the shipped package is unsigned and must be accepted
+    // with the default settings (no trusted keys)
+    #[test]
+    fn accept_shipped_package() {
+        let data = fs::read(data_folder().join(global_config_file())).unwrap();
+        let signature = read_signature(data_folder().join(global_signature_file())).unwrap();
+        assert!(signature.is_none());
+        assert_eq!(signing::verify(&data, None).unwrap(), None);
+    }
+
+    // requires the package key in `~/.kaspa-resolver`
+    #[test]
+    #[ignore]
+    fn load_shipped_default_config() {
+        let nodes = load_package(&data_folder()).unwrap();
+        assert!(!nodes.is_empty());
+    }
+}
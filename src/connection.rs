@@ -22,6 +22,7 @@ impl fmt::Display for Connection {
 pub struct Connection {
     args: Arc<Args>,
     caps: ArcSwapOption<Caps>,
+    metrics: ArcSwapOption<Metrics>,
     is_synced: AtomicBool,
     clients: AtomicU64,
     peers: AtomicU64,
@@ -33,6 +34,7 @@ pub struct Connection {
     delegate: ArcSwap<Option<Arc<Connection>>>,
     is_connected: AtomicBool,
     is_online: AtomicBool,
+    stats: Stats,
 }
 
 impl Connection {
@@ -45,14 +47,23 @@ impl Connection {
         let params = *node.params();
 
         let client = match node.transport_kind {
-            TransportKind::WrpcBorsh => {
-                rpc::kaspa::Client::try_new(WrpcEncoding::Borsh, &node.address)?
-            }
-            TransportKind::WrpcJson => {
-                rpc::kaspa::Client::try_new(WrpcEncoding::SerdeJson, &node.address)?
-            }
+            TransportKind::WrpcBorsh => rpc::kaspa::Client::try_new(
+                WrpcEncoding::Borsh,
+                &node.address,
+                node.credentials().cloned(),
+                *node.limits(),
+            )?,
+            TransportKind::WrpcJson => rpc::kaspa::Client::try_new(
+                WrpcEncoding::SerdeJson,
+                &node.address,
+                node.credentials().cloned(),
+                *node.limits(),
+            )?,
             TransportKind::Grpc => {
-                unimplemented!("gRPC support is not currently implemented")
+                return Err(Error::custom(format!(
+                    "{}: gRPC transport is not supported",
+                    node.address
+                )));
             }
         };
 
@@ -61,6 +72,7 @@ impl Connection {
         Ok(Self {
             args: args.clone(),
             caps: ArcSwapOption::new(None),
+            metrics: ArcSwapOption::new(None),
             monitor,
             params,
             node,
@@ -72,6 +84,7 @@ impl Connection {
             clients: AtomicU64::new(0),
             peers: AtomicU64::new(0),
             is_online: AtomicBool::new(false),
+            stats: Stats::default(),
         })
     }
 
@@ -80,11 +93,38 @@ impl Connection {
         self.args.verbose
     }
 
-    /// Represents the connection score, which is currently
-    /// the number of sockets (clients + peers) the node has.
+    /// Represents the connection score, which is the number of
+    /// sockets (clients + peers) the node has, increased by the
+    /// node resource pressure penalty (if metrics are enabled).
     #[inline]
     pub fn score(self: &Arc<Self>) -> u64 {
-        self.delegate().sockets()
+        let delegate = self.delegate();
+        delegate.sockets() + delegate.pressure_penalty()
+    }
+
+    /// Node process resource pressure (0.0..=1.0), available
+    /// only if metrics polling is enabled.
+    pub fn pressure(&self) -> Option<f64> {
+        let caps = self.caps.load();
+        let metrics = self.metrics.load();
+        caps.as_ref()
+            .zip(metrics.as_ref())
+            .map(|(caps, metrics)| metrics.pressure(caps, self.sockets()))
+    }
+
+    /// Score penalty expressing resource pressure as a portion of
+    /// the node capacity, making a node under pressure appear as
+    /// if it is serving a larger number of clients.
+    fn pressure_penalty(&self) -> u64 {
+        let capacity = self
+            .caps
+            .load()
+            .as_ref()
+            .map(|caps| caps.capacity)
+            .unwrap_or_default();
+        self.pressure()
+            .map(|pressure| (pressure * capacity as f64 * MetricsSettings::weight()) as u64)
+            .unwrap_or_default()
     }
 
     /// Connection availability state.
@@ -101,6 +141,17 @@ impl Connection {
             })
     }
 
+    /// Indicates if the node is connected, online, synced and has
+    /// reported its capabilities, regardless of its current load.
+    pub fn is_ready(self: &Arc<Self>) -> bool {
+        let delegate = self.delegate();
+
+        self.is_connected()
+            && delegate.is_online()
+            && delegate.is_synced()
+            && delegate.caps.load().is_some()
+    }
+
     /// Indicates if the connection RPC is connected.
     #[inline]
     pub fn is_connected(&self) -> bool {
@@ -153,6 +204,22 @@ impl Connection {
         self.caps.load().clone()
     }
 
+    /// Marks the connection as connected, online and synced
+    /// with the given capabilities.
+    #[cfg(test)]
+    pub fn set_ready(&self, caps: Caps) {
+        self.is_connected.store(true, Ordering::Relaxed);
+        self.is_online.store(true, Ordering::Relaxed);
+        self.is_synced.store(true, Ordering::Relaxed);
+        self.caps.store(Some(Arc::new(caps)));
+    }
+
+    /// Node process metrics (see [`Metrics`])
+    #[inline]
+    pub fn metrics(&self) -> Option<Arc<Metrics>> {
+        self.metrics.load().clone()
+    }
+
     /// Unique system (machine) identifier of the node.
     #[inline]
     pub fn system_id(&self) -> u64 {
@@ -163,6 +230,32 @@ impl Connection {
             .unwrap_or_default()
     }
 
+    /// Rolling RPC call statistics of this connection.
+    #[inline]
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Drain state of this connection, matched by the node uid
+    /// or the system id of the node machine.
+    pub fn drain(self: &Arc<Self>) -> Option<DrainInfo> {
+        self.monitor
+            .drains()
+            .get(self.node.uid(), self.delegate().system_id())
+    }
+
+    /// Address-derived uid of the node prior to its explicit id.
+    pub fn legacy_uid(&self) -> Option<u64> {
+        self.monitor.drains().legacy_uid(self.node.uid())
+    }
+
+    /// Indicates if the connection is draining and should
+    /// not be elected.
+    #[inline]
+    pub fn is_draining(self: &Arc<Self>) -> bool {
+        self.drain().is_some()
+    }
+
     /// Connection address (URL).
     #[inline]
     pub fn address(&self) -> &str {
@@ -222,8 +315,10 @@ impl Connection {
         delegates
     }
 
-    pub fn status(&self) -> &'static str {
-        if self.is_connected() {
+    pub fn status(self: &Arc<Self>) -> &'static str {
+        if self.is_connected() && self.is_draining() {
+            "draining"
+        } else if self.is_connected() {
             if !self.is_delegate() {
                 "delegator"
             } else if self.is_synced() {
@@ -256,6 +351,7 @@ impl Connection {
         };
 
         let mut last_connect_time: Option<Instant> = None;
+        let mut last_metrics_time: Option<Instant> = None;
 
         // use futures::StreamExt;
         loop {
@@ -285,6 +381,11 @@ impl Connection {
                         let previous = self.is_online.load(Ordering::Relaxed);
                         let online = self.update_state().await.is_ok();
                         self.is_online.store(online, Ordering::Relaxed);
+                        if !online {
+                            // discard metrics of the offline node
+                            self.metrics.store(None);
+                            last_metrics_time = None;
+                        }
                         if online != previous {
                             if self.verbose() {
                                 if online {
@@ -295,6 +396,20 @@ impl Connection {
                             }
                             self.update();
                         }
+
+                        let metrics_due = match last_metrics_time {
+                            Some(time) => time.elapsed() > MetricsSettings::poll(),
+                            None => true,
+                        };
+                        if online
+                            && self.is_delegate()
+                            && MetricsSettings::enable()
+                            && metrics_due
+                        {
+                            last_metrics_time = Some(Instant::now());
+                            self.update_metrics().await;
+                            self.update();
+                        }
                     }
                 }
 
@@ -328,11 +443,15 @@ impl Connection {
                                         self.update();
                                     } else {
                                         self.is_online.store(false, Ordering::Relaxed);
+                                        self.metrics.store(None);
+                                        last_metrics_time = None;
                                     }
                                 },
                                 Ctl::Disconnect => {
                                     self.is_connected.store(false, Ordering::Relaxed);
                                     self.is_online.store(false, Ordering::Relaxed);
+                                    self.metrics.store(None);
+                                    last_metrics_time = None;
                                     last_connect_time = None;
                                     self.update();
                                     log_error!("Disconnected","{}",self.node.address);
@@ -377,9 +496,22 @@ impl Connection {
         Ok(())
     }
 
+    /// Executes an RPC call, recording its latency and outcome
+    /// in the connection statistics.
+    async fn call<T, F>(&self, method: &'static str, call: F) -> Result<T>
+    where
+        F: std::future::Future<Output = Result<T>>,
+    {
+        let ts = Instant::now();
+        let result = call.await;
+        self.stats
+            .record(method, ts.elapsed(), result.as_ref().err());
+        result
+    }
+
     async fn update_caps(self: &Arc<Self>) -> Result<()> {
         if let Some(prev_caps) = self.caps().as_ref() {
-            let new_caps = self.client.get_caps().await?;
+            let new_caps = self.call("get_caps", self.client.get_caps()).await?;
             let caps = Caps::with_version(prev_caps, new_caps.version);
             self.caps.store(Some(Arc::new(caps)));
         }
@@ -387,9 +519,23 @@ impl Connection {
         Ok(())
     }
 
+    async fn update_metrics(self: &Arc<Self>) {
+        match self.call("get_metrics", self.client.get_metrics()).await {
+            Ok(metrics) => {
+                self.metrics.store(Some(Arc::new(metrics)));
+            }
+            Err(err) => {
+                // do not penalize the node based on stale metrics
+                self.metrics.store(None);
+                log_error!("Metrics", "{self}");
+                log_error!("Error", "{err}");
+            }
+        }
+    }
+
     async fn update_state(self: &Arc<Self>) -> Result<()> {
         if !self.is_delegate() {
-            if let Err(err) = self.client.ping().await {
+            if let Err(err) = self.call("ping", self.client.ping()).await {
                 log_error!("Ping", "{err}");
             }
             return Ok(());
@@ -397,7 +543,7 @@ impl Connection {
 
         if self.caps().is_none() {
             let last_system_id = self.caps().as_ref().map(|caps| caps.system_id());
-            let caps = self.client.get_caps().await?;
+            let caps = self.call("get_caps", self.client.get_caps()).await?;
             let system_id = caps.system_id();
             self.caps.store(Some(Arc::new(caps)));
 
@@ -413,13 +559,19 @@ impl Connection {
             }
         }
 
-        match self.client.get_sync().await {
+        match self.call("get_sync", self.client.get_sync()).await {
             Ok(is_synced) => {
                 let previous_sync = self.is_synced.load(Ordering::Relaxed);
                 self.is_synced.store(is_synced, Ordering::Relaxed);
 
                 if is_synced {
-                    match self.client.get_active_connections().await {
+                    match self
+                        .call(
+                            "get_active_connections",
+                            self.client.get_active_connections(),
+                        )
+                        .await
+                    {
                         Ok(Connections { clients, peers }) => {
                             if self.verbose() {
                                 let prev_clients = self.clients.load(Ordering::Relaxed);
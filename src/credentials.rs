@@ -0,0 +1,65 @@
+use crate::imports::*;
+
+/// Optional credentials used when connecting to nodes located
+/// behind an authenticating proxy. Credentials can be specified
+/// per node (`[node.auth]`) or per group (`[group.auth]`).
+///
+/// Credentials are secrets and should only be distributed within
+/// the encrypted `resolver.N.bin` package. They are never included
+/// in the node address, status or election output.
+///
+/// The wRPC client establishes connections from a URL only (no custom
+/// handshake request), so the token is passed as a query parameter;
+/// authenticating proxies should exclude it from their access logs.
+/// Custom handshake headers and mTLS client certificates are not
+/// supported for the same reason.
+#[derive(Default, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct Credentials {
+    // bearer token
+    pub token: Option<String>,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("token", &self.token.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+impl Credentials {
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .token
+            .as_deref()
+            .is_some_and(|token| token.trim().is_empty())
+        {
+            return Err(Error::config("Empty `token`"));
+        }
+        Ok(())
+    }
+
+    /// Produces a connection URL carrying the bearer token as the
+    /// `access_token` query parameter (RFC 6750, section 2.3).
+    /// The resulting URL must never be displayed or logged.
+    pub fn connect_url(&self, url: &str) -> String {
+        if let Some(token) = &self.token {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            format!("{url}{separator}access_token={}", urlencode(token))
+        } else {
+            url.to_string()
+        }
+    }
+}
+
+fn urlencode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
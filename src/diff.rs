@@ -0,0 +1,121 @@
+use crate::imports::*;
+use std::collections::BTreeMap;
+
+/// Difference between two node lists, used to review
+/// configuration updates before they are applied.
+#[derive(Debug, Default)]
+pub struct NodeDiff {
+    pub added: Vec<Arc<Node>>,
+    pub removed: Vec<Arc<Node>>,
+    pub before: usize,
+    pub after: usize,
+}
+
+impl NodeDiff {
+    pub fn new(before: &[Arc<Node>], after: &[Arc<Node>]) -> Self {
+        let before_uids = before.iter().map(|node| node.uid()).collect::<HashSet<_>>();
+        let after_uids = after.iter().map(|node| node.uid()).collect::<HashSet<_>>();
+
+        let added = after
+            .iter()
+            .filter(|node| !before_uids.contains(&node.uid()))
+            .cloned()
+            .collect();
+        let removed = before
+            .iter()
+            .filter(|node| !after_uids.contains(&node.uid()))
+            .cloned()
+            .collect();
+
+        Self {
+            added,
+            removed,
+            before: before.len(),
+            after: after.len(),
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Percentage of previously configured nodes removed by the update.
+    pub fn removal_pct(&self) -> f64 {
+        if self.before == 0 {
+            0.0
+        } else {
+            self.removed.len() as f64 / self.before as f64 * 100.0
+        }
+    }
+
+    /// Logs added and removed nodes grouped by service, network and transport.
+    pub fn render(&self) {
+        if self.is_empty() {
+            log_info!("Diff", "No node changes ({} nodes)", self.after);
+            return;
+        }
+
+        let mut groups = BTreeMap::<String, (Vec<&Arc<Node>>, Vec<&Arc<Node>>)>::new();
+        for node in self.added.iter() {
+            groups.entry(group_key(node)).or_default().0.push(node);
+        }
+        for node in self.removed.iter() {
+            groups.entry(group_key(node)).or_default().1.push(node);
+        }
+
+        for (group, (added, removed)) in groups {
+            log_info!("Diff", "{group}: +{} -{}", added.len(), removed.len());
+            for node in added {
+                log_success!("+", "{node}");
+            }
+            for node in removed {
+                log_error!("-", "{node}");
+            }
+        }
+
+        log_info!(
+            "Diff",
+            "{} -> {} nodes (+{} -{}, {:1.2}% removed)",
+            self.before,
+            self.after,
+            self.added.len(),
+            self.removed.len(),
+            self.removal_pct()
+        );
+    }
+
+    /// Refuses updates that remove all configured nodes and flags
+    /// updates removing more than [`Updates::max_removal_pct`] of nodes.
+    pub fn validate(&self) -> Result<()> {
+        if self.after == 0 && self.before > 0 {
+            return Err(Error::config(format!(
+                "Refusing update that removes all {} configured nodes",
+                self.before
+            )));
+        }
+
+        let removal_pct = self.removal_pct();
+        if removal_pct > Updates::max_removal_pct() {
+            log_warn!(
+                "Diff",
+                "!!! Update removes {:1.2}% of nodes ({} of {}), exceeding {:1.2}% threshold",
+                removal_pct,
+                self.removed.len(),
+                self.before,
+                Updates::max_removal_pct()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn group_key(node: &Node) -> String {
+    format!(
+        "{}/{}/{}",
+        node.service(),
+        node.network,
+        node.transport_kind()
+    )
+}
@@ -0,0 +1,451 @@
+//!
+//! DNS-based node discovery. Discovery sources (`[[discovery.source]]`
+//! settings) resolve SRV records (`srv = "_kaspa-wrpc._tcp.example.org"`)
+//! or expand the A/AAAA records of a host name (`fqdn = "nodes.example.org"`)
+//! into individually monitored nodes. Sources are re-resolved every
+//! `discovery.interval-sec` seconds. If a source fails to resolve,
+//! the previously discovered nodes of the source are retained.
+//!
+
+use crate::imports::*;
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use hickory_resolver::TokioAsyncResolver;
+use std::net::{IpAddr, SocketAddr};
+
+// default address template of discovered nodes
+const DEFAULT_TEMPLATE: &str = "${if tls}wss${else}ws${end}://${fqdn}${if port}:${port}${end}/${service}/${network}/${protocol}/${encoding}";
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct DiscoverySource {
+    // SRV record name
+    pub srv: Option<String>,
+    // host name expanded into A/AAAA records
+    pub fqdn: Option<String>,
+    pub service: Service,
+    #[schemars(with = "String")]
+    pub network: NetworkId,
+    pub transport_type: TransportKind,
+    pub tls: bool,
+    // port of A/AAAA endpoints (SRV records specify the port)
+    pub port: Option<u16>,
+    // address template (same variables as transport templates)
+    #[schemars(with = "Option<String>")]
+    pub template: Option<Template>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl Display for DiscoverySource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (&self.srv, &self.fqdn) {
+            (Some(srv), _) => write!(f, "srv:{srv}"),
+            (None, Some(fqdn)) => write!(f, "fqdn:{fqdn}"),
+            (None, None) => write!(f, "n/a"),
+        }
+    }
+}
+
+impl DiscoverySource {
+    pub fn validate(&self) -> Result<()> {
+        if self.srv.is_some() == self.fqdn.is_some() {
+            return Err(Error::config(
+                "Discovery source requires either `srv` or `fqdn`",
+            ));
+        }
+        if !self.transport_type.is_supported() {
+            return Err(Error::config(format!(
+                "Unsupported transport type `{}`",
+                self.transport_type
+            )));
+        }
+        // ports of SRV endpoints are provided by the SRV records
+        let port = if self.srv.is_some() {
+            Some(0)
+        } else {
+            self.port
+        };
+        self.transport(port)?.validate()
+    }
+
+    fn transport(&self, port: Option<u16>) -> Result<Transport> {
+        let template = match &self.template {
+            Some(template) => template.clone(),
+            None => Template::parse(DEFAULT_TEMPLATE)?,
+        };
+        Ok(Transport {
+            kind: self.transport_type,
+            tls: self.tls,
+            template,
+            ports: port.map(|port| [(self.network, port)].into_iter().collect()),
+        })
+    }
+
+    fn node(&self, fqdn: &str, port: Option<u16>) -> Result<Arc<Node>> {
+        let transport = self.transport(port.or(self.port))?;
+        let address = transport.make_address(fqdn, &self.service, &self.network)?;
+        let options = NodeOptions {
+            group: Some(self.to_string()),
+            tags: self.tags.clone().unwrap_or_default(),
+            ..Default::default()
+        };
+        Ok(Node::new(
+            &self.service,
+            self.network,
+            &transport,
+            fqdn,
+            address,
+            options,
+        ))
+    }
+
+    /// Resolves the source into a list of nodes.
+    pub async fn resolve(&self, resolver: &TokioAsyncResolver) -> Result<Vec<Arc<Node>>> {
+        let mut nodes = Vec::new();
+        if let Some(srv) = &self.srv {
+            let lookup = resolver.srv_lookup(srv.as_str()).await?;
+            for record in lookup.iter() {
+                let target = record.target().to_utf8();
+                let target = target.trim_end_matches('.');
+                nodes.push(self.node(target, Some(record.port()))?);
+            }
+        } else if let Some(fqdn) = &self.fqdn {
+            let lookup = resolver.lookup_ip(fqdn.as_str()).await?;
+            for ip in lookup.iter() {
+                let host = match ip {
+                    IpAddr::V4(ip) => ip.to_string(),
+                    IpAddr::V6(ip) => format!("[{ip}]"),
+                };
+                nodes.push(self.node(&host, None)?);
+            }
+        }
+        Ok(nodes)
+    }
+}
+
+/// Discovered nodes of all discovery sources.
+pub struct Discovery {
+    sources: Vec<DiscoverySource>,
+    // DNS servers (system configuration if empty)
+    nameservers: Vec<SocketAddr>,
+    nodes: Mutex<Vec<Vec<Arc<Node>>>>,
+    resolver: OnceLock<TokioAsyncResolver>,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        let nameservers = DiscoverySettings::nameservers().unwrap_or_else(|err| {
+            log_error!("Discovery", "{err}");
+            vec![]
+        });
+        Self::new(DiscoverySettings::sources(), nameservers)
+    }
+}
+
+impl Discovery {
+    pub fn new(sources: Vec<DiscoverySource>, nameservers: Vec<SocketAddr>) -> Self {
+        let nodes = Mutex::new(vec![vec![]; sources.len()]);
+        Self {
+            sources,
+            nameservers,
+            nodes,
+            resolver: OnceLock::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.sources.is_empty()
+    }
+
+    fn resolver(&self) -> Result<&TokioAsyncResolver> {
+        if let Some(resolver) = self.resolver.get() {
+            return Ok(resolver);
+        }
+
+        let resolver = if self.nameservers.is_empty() {
+            TokioAsyncResolver::tokio_from_system_conf()?
+        } else {
+            let mut group = NameServerConfigGroup::new();
+            for nameserver in self.nameservers.iter() {
+                group.merge(NameServerConfigGroup::from_ips_clear(
+                    &[nameserver.ip()],
+                    nameserver.port(),
+                    true,
+                ));
+            }
+            TokioAsyncResolver::tokio(
+                ResolverConfig::from_parts(None, vec![], group),
+                ResolverOpts::default(),
+            )
+        };
+        Ok(self.resolver.get_or_init(|| resolver))
+    }
+
+    /// Nodes discovered during the last resolution.
+    pub fn nodes(&self) -> Vec<Arc<Node>> {
+        self.nodes
+            .lock()
+            .unwrap()
+            .iter()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Re-resolves all sources. Returns `true` if the set
+    /// of discovered nodes has changed.
+    pub async fn resolve(&self) -> Result<bool> {
+        if !self.is_enabled() {
+            return Ok(false);
+        }
+
+        let resolver = self.resolver()?;
+        let mut changed = false;
+        for (index, source) in self.sources.iter().enumerate() {
+            match source.resolve(resolver).await {
+                Ok(nodes) => {
+                    let mut list = self.nodes.lock().unwrap();
+                    let uids = |nodes: &[Arc<Node>]| {
+                        nodes.iter().map(|node| node.uid()).collect::<HashSet<_>>()
+                    };
+                    if uids(&list[index]) != uids(&nodes) {
+                        log_info!("Discovery", "{source}: {} node(s)", nodes.len());
+                        list[index] = nodes;
+                        changed = true;
+                    }
+                }
+                Err(err) => {
+                    log_warn!("Discovery", "{source}: {err}");
+                }
+            }
+        }
+
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_proto::op::{Message, MessageType, ResponseCode};
+    use hickory_proto::rr::rdata::{A, AAAA, SRV};
+    use hickory_proto::rr::{Name, RData, Record, RecordType};
+    use hickory_proto::serialize::binary::{BinDecodable, BinEncodable};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use tokio::net::UdpSocket;
+
+    // stub DNS server answering from a record table; names
+    // flagged as failing are answered with SERVFAIL
+    #[derive(Clone, Default)]
+    struct Stub {
+        records: Arc<Mutex<HashMap<(String, RecordType), Vec<RData>>>>,
+        failing: Arc<Mutex<HashSet<String>>>,
+    }
+
+    impl Stub {
+        fn set(&self, name: &str, record_type: RecordType, rdata: Vec<RData>) {
+            self.records
+                .lock()
+                .unwrap()
+                .insert((name.to_string(), record_type), rdata);
+        }
+
+        fn fail(&self, name: &str) {
+            self.failing.lock().unwrap().insert(name.to_string());
+        }
+
+        async fn start(&self) -> SocketAddr {
+            let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let addr = socket.local_addr().unwrap();
+            let stub = self.clone();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 1500];
+                while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                    let Ok(request) = Message::from_vec(&buf[..len]) else {
+                        continue;
+                    };
+                    let response = stub.respond(&request).to_vec().unwrap();
+                    let _ = socket.send_to(&response, peer).await;
+                }
+            });
+            addr
+        }
+
+        fn respond(&self, request: &Message) -> Message {
+            let mut response = Message::new();
+            response
+                .set_id(request.id())
+                .set_message_type(MessageType::Response)
+                .set_op_code(request.op_code())
+                .set_recursion_desired(request.recursion_desired())
+                .set_recursion_available(true);
+            response.add_queries(request.queries().to_vec());
+
+            for query in request.queries() {
+                let name = query.name().to_utf8();
+                let name = name.trim_end_matches('.');
+                if self.failing.lock().unwrap().contains(name) {
+                    response.set_response_code(ResponseCode::ServFail);
+                    continue;
+                }
+                let records = self.records.lock().unwrap();
+                for rdata in records
+                    .get(&(name.to_string(), query.query_type()))
+                    .into_iter()
+                    .flatten()
+                {
+                    // zero TTL prevents caching between resolutions
+                    response.add_answer(Record::from_rdata(query.name().clone(), 0, rdata.clone()));
+                }
+            }
+            response
+        }
+    }
+
+    fn srv(port: u16, target: &str) -> RData {
+        RData::SRV(SRV::new(0, 0, port, Name::from_ascii(target).unwrap()))
+    }
+
+    fn source(srv: Option<&str>, fqdn: Option<&str>, port: Option<u16>) -> DiscoverySource {
+        DiscoverySource {
+            srv: srv.map(String::from),
+            fqdn: fqdn.map(String::from),
+            service: Service::Kaspa,
+            network: NetworkId::new(NetworkType::Mainnet),
+            transport_type: TransportKind::WrpcBorsh,
+            tls: false,
+            port,
+            template: None,
+            tags: None,
+        }
+    }
+
+    fn addresses(discovery: &Discovery) -> Vec<String> {
+        let mut addresses = discovery
+            .nodes()
+            .iter()
+            .map(|node| node.address().to_string())
+            .collect::<Vec<_>>();
+        addresses.sort();
+        addresses
+    }
+
+    #[tokio::test]
+    async fn srv_records_expand_with_record_ports() {
+        let stub = Stub::default();
+        stub.set(
+            "_kaspa._tcp.example.test",
+            RecordType::SRV,
+            vec![
+                srv(17110, "node1.example.test."),
+                srv(17111, "node2.example.test."),
+            ],
+        );
+        let nameserver = stub.start().await;
+
+        let source = source(Some("_kaspa._tcp.example.test"), None, None);
+        source.validate().unwrap();
+        let discovery = Discovery::new(vec![source], vec![nameserver]);
+        assert!(discovery.resolve().await.unwrap());
+        assert_eq!(
+            addresses(&discovery),
+            vec![
+                "ws://node1.example.test:17110/kaspa/mainnet/wrpc/borsh",
+                "ws://node2.example.test:17111/kaspa/mainnet/wrpc/borsh",
+            ]
+        );
+        assert!(discovery
+            .nodes()
+            .iter()
+            .all(|node| node.group() == Some("srv:_kaspa._tcp.example.test")));
+    }
+
+    #[tokio::test]
+    async fn a_and_aaaa_records_expand_into_hosts() {
+        let stub = Stub::default();
+        stub.set(
+            "v4.example.test",
+            RecordType::A,
+            vec![
+                RData::A(A(Ipv4Addr::new(10, 0, 0, 1))),
+                RData::A(A(Ipv4Addr::new(10, 0, 0, 2))),
+            ],
+        );
+        stub.set(
+            "v6.example.test",
+            RecordType::AAAA,
+            vec![RData::AAAA(AAAA(Ipv6Addr::LOCALHOST))],
+        );
+        let nameserver = stub.start().await;
+
+        let discovery = Discovery::new(
+            vec![
+                source(None, Some("v4.example.test"), Some(16110)),
+                source(None, Some("v6.example.test"), Some(16110)),
+            ],
+            vec![nameserver],
+        );
+        assert!(discovery.resolve().await.unwrap());
+        assert_eq!(
+            addresses(&discovery),
+            vec![
+                "ws://10.0.0.1:16110/kaspa/mainnet/wrpc/borsh",
+                "ws://10.0.0.2:16110/kaspa/mainnet/wrpc/borsh",
+                "ws://[::1]:16110/kaspa/mainnet/wrpc/borsh",
+            ]
+        );
+        assert!(discovery.nodes().iter().any(|node| node.fqdn == "[::1]"));
+    }
+
+    #[tokio::test]
+    async fn failing_source_retains_previous_nodes() {
+        let stub = Stub::default();
+        stub.set(
+            "nodes.example.test",
+            RecordType::A,
+            vec![RData::A(A(Ipv4Addr::new(10, 0, 0, 1)))],
+        );
+        let nameserver = stub.start().await;
+
+        let discovery = Discovery::new(
+            vec![source(None, Some("nodes.example.test"), Some(16110))],
+            vec![nameserver],
+        );
+        assert!(discovery.resolve().await.unwrap());
+        let before = addresses(&discovery);
+        assert_eq!(before.len(), 1);
+
+        stub.fail("nodes.example.test");
+        assert!(!discovery.resolve().await.unwrap());
+        assert_eq!(addresses(&discovery), before);
+    }
+
+    #[tokio::test]
+    async fn resolve_reports_changes_only() {
+        let stub = Stub::default();
+        stub.set(
+            "nodes.example.test",
+            RecordType::A,
+            vec![RData::A(A(Ipv4Addr::new(10, 0, 0, 1)))],
+        );
+        let nameserver = stub.start().await;
+
+        let discovery = Discovery::new(
+            vec![source(None, Some("nodes.example.test"), Some(16110))],
+            vec![nameserver],
+        );
+        assert!(discovery.resolve().await.unwrap());
+        assert!(!discovery.resolve().await.unwrap());
+
+        stub.set(
+            "nodes.example.test",
+            RecordType::A,
+            vec![
+                RData::A(A(Ipv4Addr::new(10, 0, 0, 1))),
+                RData::A(A(Ipv4Addr::new(10, 0, 0, 2))),
+            ],
+        );
+        assert!(discovery.resolve().await.unwrap());
+        assert_eq!(addresses(&discovery).len(), 2);
+        assert!(!discovery.resolve().await.unwrap());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::imports::*;
+use std::time::SystemTime;
+
+/// Drain target: a node connection (`uid`) or all connections
+/// of a machine identified by its system id (`sid`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DrainTarget {
+    Uid(#[serde(with = "SerHex::<Strict>")] u64),
+    Sid(#[serde(with = "SerHex::<Strict>")] u64),
+}
+
+impl Display for DrainTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DrainTarget::Uid(uid) => write!(f, "uid:{uid:016x}"),
+            DrainTarget::Sid(sid) => write!(f, "sid:{sid:016x}"),
+        }
+    }
+}
+
+impl FromStr for DrainTarget {
+    type Err = Error;
+
+    /// Parses `uid:<hex>` or `sid:<hex>` target specification.
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, id) = s.split_once(':').ok_or_else(|| {
+            Error::custom(format!(
+                "Invalid drain target `{s}`, expected `uid:<hex>` or `sid:<hex>`"
+            ))
+        })?;
+        let id = u64::from_str_radix(id.trim(), 16)
+            .map_err(|_| Error::custom(format!("Invalid drain target id: `{id}`")))?;
+        match kind.trim() {
+            "uid" => Ok(DrainTarget::Uid(id)),
+            "sid" => Ok(DrainTarget::Sid(id)),
+            _ => Err(Error::custom(format!(
+                "Invalid drain target kind: `{kind}`"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrainInfo {
+    // operator that has set the drain state
+    pub by: String,
+    // unix timestamp (seconds)
+    pub ts: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DrainRecord {
+    target: DrainTarget,
+    #[serde(flatten)]
+    info: DrainInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LegacyUid {
+    #[serde(with = "SerHex::<Strict>")]
+    uid: u64,
+    // address-derived uid of the node when its id was first seen
+    #[serde(with = "SerHex::<Strict>")]
+    legacy: u64,
+}
+
+/// Drain state of nodes. Draining nodes remain monitored
+/// but are excluded from elections. The drain state is
+/// persisted in `~/.kaspa-resolver/drain.json`, legacy
+/// uids of nodes with an explicit id are persisted in
+/// `~/.kaspa-resolver/uids.json`.
+#[derive(Debug, Default)]
+pub struct Drains {
+    drains: RwLock<AHashMap<DrainTarget, DrainInfo>>,
+    legacy: RwLock<AHashMap<u64, u64>>,
+}
+
+impl Drains {
+    fn path() -> PathBuf {
+        global_config_folder().join("drain.json")
+    }
+
+    fn legacy_path() -> PathBuf {
+        global_config_folder().join("uids.json")
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        let drains = if path.exists() {
+            let json = std::fs::read_to_string(&path).map_err(|err| Error::file(&path, err))?;
+            serde_json::from_str::<Vec<DrainRecord>>(&json)?
+                .into_iter()
+                .map(|DrainRecord { target, info }| (target, info))
+                .collect()
+        } else {
+            AHashMap::default()
+        };
+
+        for target in drains.keys() {
+            log_warn!("Drain", "{target}");
+        }
+
+        let path = Self::legacy_path();
+        let legacy = if path.exists() {
+            let json = std::fs::read_to_string(&path).map_err(|err| Error::file(&path, err))?;
+            serde_json::from_str::<Vec<LegacyUid>>(&json)?
+                .into_iter()
+                .map(|LegacyUid { uid, legacy }| (uid, legacy))
+                .collect()
+        } else {
+            AHashMap::default()
+        };
+
+        Ok(Self {
+            drains: RwLock::new(drains),
+            legacy: RwLock::new(legacy),
+        })
+    }
+
+    fn store_legacy(&self) -> Result<()> {
+        let mut records = self
+            .legacy
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(uid, legacy)| LegacyUid {
+                uid: *uid,
+                legacy: *legacy,
+            })
+            .collect::<Vec<_>>();
+        records.sort_by_key(|record| record.uid);
+        let json = serde_json::to_string_pretty(&records)?;
+        let path = Self::legacy_path();
+        std::fs::write(&path, json).map_err(|err| Error::file(&path, err))
+    }
+
+    /// Address-derived uid a node with an explicit id had
+    /// before the id has been assigned.
+    pub fn legacy_uid(&self, uid: u64) -> Option<u64> {
+        self.legacy.read().unwrap().get(&uid).copied()
+    }
+
+    fn store(&self) -> Result<()> {
+        let records = self.list();
+        let json = serde_json::to_string_pretty(&records)?;
+        let path = Self::path();
+        std::fs::write(&path, json).map_err(|err| Error::file(&path, err))
+    }
+
+    /// Set or clear drain state of the target.
+    pub fn set(&self, target: DrainTarget, drain: bool, by: &str) -> Result<()> {
+        if drain {
+            let ts = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let info = DrainInfo {
+                by: by.to_string(),
+                ts,
+            };
+            self.drains.write().unwrap().insert(target, info);
+            log_warn!("Drain", "{target} (by: {by})");
+        } else {
+            self.drains.write().unwrap().remove(&target);
+            log_success!("Drain", "{target} cleared (by: {by})");
+        }
+        self.store()
+    }
+
+    /// Drain state of a connection matching either its
+    /// node uid or the system id of the node machine.
+    pub fn get(&self, uid: u64, system_id: u64) -> Option<DrainInfo> {
+        let drains = self.drains.read().unwrap();
+        drains
+            .get(&DrainTarget::Uid(uid))
+            .or_else(|| {
+                (system_id != 0)
+                    .then(|| drains.get(&DrainTarget::Sid(system_id)))
+                    .flatten()
+            })
+            .cloned()
+    }
+
+    /// Moves drain state recorded for address-derived uids to the
+    /// uids of nodes that have been assigned an explicit id. The
+    /// address-derived uid is recorded when the id is first seen,
+    /// so the legacy uid is retained across later address changes.
+    pub fn migrate(&self, nodes: &[Arc<Node>]) -> Result<()> {
+        let mut recorded = false;
+        let mut migrated = false;
+        {
+            let mut legacy = self.legacy.write().unwrap();
+            let mut drains = self.drains.write().unwrap();
+            for node in nodes.iter().filter(|node| node.id().is_some()) {
+                let legacy_uid = *legacy.entry(node.uid()).or_insert_with(|| {
+                    recorded = true;
+                    node.address_uid()
+                });
+                if legacy_uid == node.uid() {
+                    continue;
+                }
+                if let Some(info) = drains.remove(&DrainTarget::Uid(legacy_uid)) {
+                    let target = DrainTarget::Uid(node.uid());
+                    log_info!("Drain", "Migrating uid:{legacy_uid:016x} to {target}");
+                    drains.entry(target).or_insert(info);
+                    migrated = true;
+                }
+            }
+        }
+        if recorded {
+            self.store_legacy()?;
+        }
+        if migrated {
+            self.store()?;
+        }
+        Ok(())
+    }
+
+    fn list(&self) -> Vec<DrainRecord> {
+        let mut records = self
+            .drains
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(target, info)| DrainRecord {
+                target: *target,
+                info: info.clone(),
+            })
+            .collect::<Vec<_>>();
+        records.sort_by_key(|record| record.info.ts);
+        records
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self.list())?)
+    }
+}
@@ -0,0 +1,257 @@
+//!
+//! Versioned (`/v3/`) election API. Responses use a stable JSON
+//! envelope carrying either the elected node or a typed error with
+//! a reason code. Transient failures (`no-capacity`, `all-offline`)
+//! include a `Retry-After` hint (also sent as an HTTP header).
+//!
+//! ```json
+//! { "version": 3, "elected": { "uid": "…", "url": "wss://…" } }
+//! { "version": 3, "error": { "code": "all-offline", "message": "…", "retry-after": 30 } }
+//! ```
+//!
+
+use crate::imports::*;
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+pub const API_VERSION: u32 = 3;
+
+// retry hint for elections failing due to saturated nodes
+const NO_CAPACITY_RETRY_SEC: u64 = 5;
+// retry hint for elections failing due to unavailable nodes
+const ALL_OFFLINE_RETRY_SEC: u64 = 30;
+
+/// Reason of an unsuccessful election.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Reason {
+    // nodes are online and synced but at capacity, draining or disabled
+    NoCapacity,
+    // no node of the network and transport is online and synced
+    AllOffline,
+    // no nodes are configured for the network
+    UnknownNetwork,
+    // nodes of the network exist, but none matches the
+    // requested tls, protocol and encoding
+    ConstraintUnsatisfied,
+    // malformed request path
+    InvalidRequest,
+}
+
+impl Reason {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Reason::NoCapacity | Reason::AllOffline => StatusCode::SERVICE_UNAVAILABLE,
+            Reason::UnknownNetwork | Reason::ConstraintUnsatisfied => StatusCode::NOT_FOUND,
+            Reason::InvalidRequest => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            Reason::NoCapacity => Some(NO_CAPACITY_RETRY_SEC),
+            Reason::AllOffline => Some(ALL_OFFLINE_RETRY_SEC),
+            _ => None,
+        }
+    }
+
+    fn message(&self) -> &'static str {
+        match self {
+            Reason::NoCapacity => "All nodes are at capacity",
+            Reason::AllOffline => "No nodes are currently online",
+            Reason::UnknownNetwork => "Network is not served by this resolver",
+            Reason::ConstraintUnsatisfied => "No nodes match the requested transport",
+            Reason::InvalidRequest => "Invalid request",
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ElectionError {
+    code: Reason,
+    message: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<PathError>,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    elected: Option<Output<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ElectionError>,
+}
+
+/// Responds with the election result.
+pub fn respond(result: std::result::Result<Arc<Connection>, Reason>) -> Response<Body> {
+    match result {
+        Ok(connection) => {
+            let envelope = Envelope {
+                version: API_VERSION,
+                elected: Some(Output::from(&connection)),
+                error: None,
+            };
+            with_envelope(StatusCode::OK, &envelope, None)
+        }
+        Err(reason) => with_error(reason, None),
+    }
+}
+
+/// Responds with a path deserialization error.
+pub fn invalid_request(error: PathError) -> Response<Body> {
+    with_error(Reason::InvalidRequest, Some(error))
+}
+
+fn with_error(reason: Reason, details: Option<PathError>) -> Response<Body> {
+    let retry_after = reason.retry_after();
+    let envelope = Envelope {
+        version: API_VERSION,
+        elected: None,
+        error: Some(ElectionError {
+            code: reason,
+            message: reason.message(),
+            retry_after,
+            details,
+        }),
+    };
+    with_envelope(reason.status(), &envelope, retry_after)
+}
+
+fn with_envelope(
+    status: StatusCode,
+    envelope: &Envelope<'_>,
+    retry_after: Option<u64>,
+) -> Response<Body> {
+    let json = serde_json::to_string(envelope).unwrap_or_default();
+    let mut response = (
+        status,
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+            ),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static(
+                    "no-cache, no-store, must-revalidate, proxy-revalidate, max-age=0",
+                ),
+            ),
+            (header::CONNECTION, HeaderValue::from_static("close")),
+        ],
+        json,
+    )
+        .into_response();
+    if let Some(retry_after) = retry_after {
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    async fn body(response: Response<Body>) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn retry_after(response: &Response<Body>) -> Option<&str> {
+        response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .map(|value| value.to_str().unwrap())
+    }
+
+    #[test]
+    fn reason_status_mapping() {
+        for (reason, status, retry_after) in [
+            (Reason::NoCapacity, StatusCode::SERVICE_UNAVAILABLE, Some(5)),
+            (
+                Reason::AllOffline,
+                StatusCode::SERVICE_UNAVAILABLE,
+                Some(30),
+            ),
+            (Reason::UnknownNetwork, StatusCode::NOT_FOUND, None),
+            (Reason::ConstraintUnsatisfied, StatusCode::NOT_FOUND, None),
+            (Reason::InvalidRequest, StatusCode::BAD_REQUEST, None),
+        ] {
+            assert_eq!(reason.status(), status, "{reason:?}");
+            assert_eq!(reason.retry_after(), retry_after, "{reason:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_errors_carry_retry_after() {
+        let response = respond(Err(Reason::AllOffline));
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(retry_after(&response), Some("30"));
+        assert_eq!(
+            body(response).await,
+            json!({
+                "version": API_VERSION,
+                "error": {
+                    "code": "all-offline",
+                    "message": Reason::AllOffline.message(),
+                    "retry-after": 30,
+                },
+            })
+        );
+
+        let response = respond(Err(Reason::NoCapacity));
+        assert_eq!(retry_after(&response), Some("5"));
+        assert_eq!(body(response).await["error"]["code"], "no-capacity");
+    }
+
+    #[tokio::test]
+    async fn permanent_errors_omit_retry_after() {
+        for (reason, code) in [
+            (Reason::UnknownNetwork, "unknown-network"),
+            (Reason::ConstraintUnsatisfied, "constraint-unsatisfied"),
+        ] {
+            let response = respond(Err(reason));
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            assert_eq!(retry_after(&response), None);
+            let body = body(response).await;
+            assert_eq!(body["error"]["code"], code);
+            assert!(body["error"].get("retry-after").is_none());
+            assert!(body.get("elected").is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_request_includes_details() {
+        let response = invalid_request(PathError {
+            message: "Invalid URL: unknown variant `carrier`".to_string(),
+            location: Some("encoding".to_string()),
+        });
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(retry_after(&response), None);
+        assert_eq!(
+            body(response).await,
+            json!({
+                "version": API_VERSION,
+                "error": {
+                    "code": "invalid-request",
+                    "message": Reason::InvalidRequest.message(),
+                    "details": {
+                        "message": "Invalid URL: unknown variant `carrier`",
+                        "location": "encoding",
+                    },
+                },
+            })
+        );
+    }
+}
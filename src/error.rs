@@ -49,7 +49,7 @@ pub enum Error {
     #[error("Incompatible connection protocol encoding")]
     ConnectionProtocolEncoding,
 
-    #[error("Configuration error")]
+    #[error("Configuration error: {0}")]
     Config(String),
 
     #[error(transparent)]
@@ -72,6 +72,12 @@ pub enum Error {
 
     #[error("Unauthorized")]
     Unauthorized,
+
+    #[error("Signature error: {0}")]
+    Signature(&'static str),
+
+    #[error("DNS error: {0}")]
+    Dns(#[from] hickory_resolver::error::ResolveError),
 }
 
 impl Error {
@@ -80,6 +86,22 @@ impl Error {
     }
 }
 
+impl Error {
+    /// Short error classification used in connection statistics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::KaspaRpc(_) | Error::KaspaRpcCore(_) | Error::SparkleRpc(_) => "rpc",
+            Error::Metrics => "metrics",
+            Error::Sync => "sync",
+            Error::Status => "status",
+            Error::Io(_) | Error::File(..) => "io",
+            Error::Reqwest(_) | Error::Http(..) => "http",
+            Error::Dns(_) => "dns",
+            _ => "other",
+        }
+    }
+}
+
 impl Error {
     pub fn file<P: AsRef<std::path::Path>>(path: P, err: std::io::Error) -> Self {
         Error::File(path.as_ref().display().to_string(), err)
@@ -90,6 +112,15 @@ impl Error {
     pub fn config<T: std::fmt::Display>(msg: T) -> Self {
         Error::Config(msg.to_string())
     }
+
+    /// Configuration error with the message prefixed by `context`
+    /// (such as the config file location of the failing entry).
+    pub fn context<T: std::fmt::Display>(self, context: T) -> Self {
+        match self {
+            Error::Config(msg) => Error::Config(format!("{context}: {msg}")),
+            err => Error::Config(format!("{context}: {err}")),
+        }
+    }
 }
 
 impl<T> From<workflow_core::channel::SendError<T>> for Error {
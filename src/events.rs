@@ -1,4 +1,8 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Events {
     Start,
     Update,
+    Reload,
+    Discover,
+    Refresh,
 }
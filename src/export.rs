@@ -0,0 +1,136 @@
+//!
+//! Export of the effective (expanded) node list as JSON, TOML or CSV
+//! (`resolver export` and the `/status/config` endpoint).
+//!
+
+use crate::imports::*;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Json,
+    Toml,
+    Csv,
+}
+
+impl Format {
+    pub fn mime(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Toml => "application/toml",
+            Format::Csv => "text/csv",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "toml" => Ok(Format::Toml),
+            "csv" => Ok(Format::Csv),
+            _ => Err(Error::custom(format!(
+                "Unknown export format `{s}`, expected `json`, `toml` or `csv`"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct NodeRecord<'a> {
+    uid: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<&'a str>,
+    fqdn: &'a str,
+    address: &'a str,
+    service: String,
+    network: String,
+    transport: String,
+    tls: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<&'a str>,
+}
+
+impl<'a> From<&'a Node> for NodeRecord<'a> {
+    fn from(node: &'a Node) -> Self {
+        Self {
+            uid: node.uid_as_str(),
+            id: node.id(),
+            fqdn: &node.fqdn,
+            address: node.address(),
+            service: node.service().to_string(),
+            network: node.network.to_string(),
+            transport: node.transport_kind().to_string(),
+            tls: node.params().tls() == TlsKind::Tls,
+            group: node.group(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct NodeList<'a> {
+    node: Vec<NodeRecord<'a>>,
+}
+
+const CSV_HEADER: &str = "uid,id,fqdn,address,service,network,transport,tls,group";
+
+/// Renders the node list in the given format.
+pub fn render<N: AsRef<Node>>(nodes: &[N], format: Format) -> Result<String> {
+    let node = nodes
+        .iter()
+        .map(|node| NodeRecord::from(node.as_ref()))
+        .collect::<Vec<_>>();
+
+    match format {
+        Format::Json => Ok(serde_json::to_string_pretty(&node)?),
+        Format::Toml => toml::to_string(&NodeList { node })
+            .map_err(|err| Error::custom(format!("Unable to serialize node list: {err}"))),
+        Format::Csv => {
+            let mut csv = vec![CSV_HEADER.to_string()];
+            csv.extend(node.iter().map(|record| {
+                [
+                    record.uid,
+                    record.id.unwrap_or_default(),
+                    record.fqdn,
+                    record.address,
+                    &record.service,
+                    &record.network,
+                    &record.transport,
+                    if record.tls { "true" } else { "false" },
+                    record.group.unwrap_or_default(),
+                ]
+                .iter()
+                .map(|field| csv_field(field))
+                .collect::<Vec<_>>()
+                .join(",")
+            }));
+            csv.push(String::new());
+            Ok(csv.join("\n"))
+        }
+    }
+}
+
+// quote fields containing separators or quotes (RFC 4180)
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Prints the effective node list (`resolver export`). Uses the
+/// custom config file if specified, otherwise the installed config.
+pub fn export(format: Format) -> Result<()> {
+    let nodes = match user_config() {
+        Some(nodes) => nodes,
+        None => load_config()?,
+    };
+    print!("{}", render(&nodes, format)?);
+    if format != Format::Csv {
+        println!();
+    }
+    Ok(())
+}
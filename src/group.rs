@@ -1,12 +1,44 @@
 use crate::imports::*;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+// maximum number of ids produced by a single range
+const MAX_RANGE_LEN: usize = 4096;
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Group {
     pub enable: Option<bool>,
     pub fqdn: String,
     pub transports: Vec<String>,
     pub services: Vec<Service>,
+    // member ids per network; ids can specify ranges such as `n[01-24]`
+    #[schemars(with = "HashMap<String, Vec<String>>")]
     pub network: HashMap<NetworkId, Vec<String>>,
+    // member ids (or ranges) excluded from all networks
+    pub exclude: Option<Vec<String>>,
+    // tags shared by all group members
+    pub tags: Option<Vec<String>>,
+    // per-member overrides keyed by member id
+    #[serde(rename = "member")]
+    pub members: Option<HashMap<String, MemberOverride>>,
+    // optional credentials shared by all group members
+    pub auth: Option<Credentials>,
+    // capacity and election overrides shared by all group members
+    #[serde(flatten)]
+    pub limits: NodeLimits,
+}
+
+/// Overrides applied to a single group member (`[group.member.<id>]`).
+#[derive(Default, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct MemberOverride {
+    // stable node id (see [`NodeConfig::id`])
+    pub id: Option<String>,
+    pub enable: Option<bool>,
+    pub weight: Option<f64>,
+    // tags appended to the group tags
+    pub tags: Option<Vec<String>>,
+    // custom address template (same variables as transport templates)
+    #[schemars(with = "Option<String>")]
+    pub address: Option<Template>,
 }
 
 impl std::fmt::Display for Group {
@@ -14,3 +46,176 @@ impl std::fmt::Display for Group {
         write!(f, "{}", self.fqdn)
     }
 }
+
+impl Group {
+    /// Expands member ids of a network, resolving ranges and
+    /// removing excluded ids.
+    pub fn member_ids(&self, ids: &[String]) -> Result<Vec<String>> {
+        let exclude = self
+            .exclude
+            .iter()
+            .flatten()
+            .map(|spec| expand_range(spec.as_str()))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<HashSet<_>>();
+
+        let mut unique = HashSet::new();
+        let mut members = Vec::new();
+        for spec in ids {
+            for id in expand_range(spec)? {
+                if !unique.insert(id.clone()) {
+                    return Err(Error::config(format!("Duplicate member id `{id}`")));
+                }
+                if !exclude.contains(&id) {
+                    members.push(id);
+                }
+            }
+        }
+
+        Ok(members)
+    }
+
+    /// Ensures that member overrides refer to existing members.
+    pub fn validate_members(&self) -> Result<()> {
+        let Some(members) = &self.members else {
+            return Ok(());
+        };
+
+        let mut ids = HashSet::new();
+        for list in self.network.values() {
+            ids.extend(self.member_ids(list)?);
+        }
+
+        for (id, member) in members.iter() {
+            if !ids.contains(id) {
+                return Err(Error::config(format!(
+                    "Member override `{id}` does not match any group member"
+                )));
+            }
+            if member.id.as_deref().is_some_and(|id| id.trim().is_empty()) {
+                return Err(Error::config(format!("Member `{id}`: empty node id")));
+            }
+            if let Some(address) = &member.address {
+                address
+                    .validate(Transport::VARIABLES)
+                    .map_err(|err| err.context(format!("Member `{id}`")))?;
+            }
+            if let Some(weight) = member.weight {
+                if !weight.is_finite() || weight < 0.0 {
+                    return Err(Error::config(format!(
+                        "Member `{id}`: invalid weight: {weight}"
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn member(&self, id: &str) -> Option<&MemberOverride> {
+        self.members.as_ref().and_then(|members| members.get(id))
+    }
+}
+
+/// Expands a member id range such as `n[01-24]` into individual ids
+/// (`n01`, `n02`, ... `n24`). Zero padding of the range start is
+/// preserved. Ids without a range are returned as is.
+pub fn expand_range(spec: &str) -> Result<Vec<String>> {
+    let Some((prefix, rest)) = spec.split_once('[') else {
+        return Ok(vec![spec.to_string()]);
+    };
+
+    let invalid = || Error::config(format!("Invalid member id range `{spec}`"));
+
+    let (range, suffix) = rest.split_once(']').ok_or_else(invalid)?;
+    let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+    let (start, end) = (start.trim(), end.trim());
+    let first = start.parse::<u64>().map_err(|_| invalid())?;
+    let last = end.parse::<u64>().map_err(|_| invalid())?;
+    if first > last || (last - first) as usize >= MAX_RANGE_LEN || suffix.contains('[') {
+        return Err(invalid());
+    }
+
+    let width = if start.starts_with('0') {
+        start.len()
+    } else {
+        0
+    };
+    Ok((first..=last)
+        .map(|n| format!("{prefix}{n:0width$}{suffix}"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(toml: &str) -> Group {
+        toml::from_str(&format!(
+            r#"
+            fqdn = "*.example.com"
+            transports = ["wrpc-borsh-tls"]
+            services = ["kaspa"]
+            {toml}
+            [network]
+            "#
+        ))
+        .expect("group")
+    }
+
+    fn ids(list: &[&str]) -> Vec<String> {
+        list.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn expand_plain_id() {
+        assert_eq!(expand_range("node").unwrap(), ids(&["node"]));
+    }
+
+    #[test]
+    fn expand_preserves_zero_padding() {
+        let expanded = expand_range("n[08-11]").unwrap();
+        assert_eq!(expanded, ids(&["n08", "n09", "n10", "n11"]));
+
+        let expanded = expand_range("n[8-11].eu").unwrap();
+        assert_eq!(expanded, ids(&["n8.eu", "n9.eu", "n10.eu", "n11.eu"]));
+    }
+
+    #[test]
+    fn expand_rejects_invalid_ranges() {
+        for spec in ["n[01-", "n[01]", "n[a-b]", "n[5-1]", "n[1-2][3-4]"] {
+            assert!(expand_range(spec).is_err(), "{spec}");
+        }
+    }
+
+    #[test]
+    fn expand_limits_range_length() {
+        let last = MAX_RANGE_LEN - 1;
+        assert_eq!(
+            expand_range(&format!("n[0-{last}]")).unwrap().len(),
+            MAX_RANGE_LEN
+        );
+        assert!(expand_range(&format!("n[0-{MAX_RANGE_LEN}]")).is_err());
+    }
+
+    #[test]
+    fn member_ids_apply_exclusions() {
+        let group = group(r#"exclude = ["n02", "n[04-05]"]"#);
+        let members = group.member_ids(&ids(&["n[01-05]", "x"])).unwrap();
+        assert_eq!(members, ids(&["n01", "n03", "x"]));
+    }
+
+    #[test]
+    fn member_ids_reject_duplicates() {
+        let err = group("")
+            .member_ids(&ids(&["n[01-03]", "n02"]))
+            .unwrap_err();
+        assert!(err.to_string().contains("Duplicate member id `n02`"));
+
+        // excluded ids are still checked for duplicates
+        let excluding = group(r#"exclude = ["n02"]"#);
+        assert!(excluding.member_ids(&ids(&["n02", "n[01-02]"])).is_err());
+    }
+}
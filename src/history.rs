@@ -0,0 +1,263 @@
+//!
+//! Configuration history. The last `updates.history` applied packages
+//! are retained in `~/.kaspa-resolver/history` along with their hash,
+//! timestamp and source. A package can be pinned, in which case
+//! updates apply the pinned package instead of the fetched one.
+//!
+
+use crate::imports::*;
+use crate::sources::Package;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub hash: String,
+    // unix timestamp (seconds) when the package was applied
+    pub ts: u64,
+    pub source: String,
+    pub publisher: Option<String>,
+}
+
+impl Display for HistoryEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let ts = chrono::DateTime::from_timestamp(self.ts as i64, 0)
+            .map(|ts| ts.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        write!(f, "{}  {ts}  {}", self.hash, self.source)?;
+        if let Some(publisher) = &self.publisher {
+            write!(f, "  (signed by {publisher})")?;
+        }
+        Ok(())
+    }
+}
+
+/// History entries (most recent first) and the pinned package hash.
+#[derive(Debug, Serialize)]
+pub struct History {
+    pub pinned: Option<String>,
+    pub entries: Vec<HistoryEntry>,
+}
+
+fn folder() -> Result<PathBuf> {
+    let folder = global_config_folder().join("history");
+    if !folder.exists() {
+        std::fs::create_dir_all(&folder).map_err(|err| Error::file(&folder, err))?;
+    }
+    Ok(folder)
+}
+
+fn index_file() -> Result<PathBuf> {
+    Ok(folder()?.join("index.json"))
+}
+
+fn pin_file() -> Result<PathBuf> {
+    Ok(folder()?.join("pin"))
+}
+
+fn data_file(hash: &str) -> Result<PathBuf> {
+    Ok(folder()?.join(format!("{hash}.bin")))
+}
+
+fn signature_file(hash: &str) -> Result<PathBuf> {
+    Ok(folder()?.join(format!("{hash}.sig")))
+}
+
+fn entries() -> Result<Vec<HistoryEntry>> {
+    let path = index_file()?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let json = std::fs::read_to_string(&path).map_err(|err| Error::file(&path, err))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+fn store(entries: &[HistoryEntry]) -> Result<()> {
+    let path = index_file()?;
+    let json = serde_json::to_string_pretty(entries)?;
+    std::fs::write(&path, json).map_err(|err| Error::file(&path, err))
+}
+
+fn remove_files(hash: &str) -> Result<()> {
+    for path in [data_file(hash)?, signature_file(hash)?] {
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|err| Error::file(&path, err))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn history() -> Result<History> {
+    Ok(History {
+        pinned: pinned()?,
+        entries: entries()?,
+    })
+}
+
+/// Records an applied package, retaining the last `updates.history`
+/// packages (the pinned package is never removed).
+pub fn record(package: &Package) -> Result<()> {
+    let ts = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let data_path = data_file(&package.hash)?;
+    std::fs::write(&data_path, &package.data).map_err(|err| Error::file(&data_path, err))?;
+    if let Some(signature) = &package.signature {
+        let path = signature_file(&package.hash)?;
+        std::fs::write(&path, signature).map_err(|err| Error::file(&path, err))?;
+    }
+
+    let mut entries = entries()?;
+    entries.retain(|entry| entry.hash != package.hash);
+    entries.insert(
+        0,
+        HistoryEntry {
+            hash: package.hash.clone(),
+            ts,
+            source: package.source.clone(),
+            publisher: package.publisher.clone(),
+        },
+    );
+
+    let pinned = pinned()?;
+    let mut retained = 0;
+    let mut expired = vec![];
+    entries.retain(|entry| {
+        if pinned.as_deref() == Some(entry.hash.as_str()) {
+            true
+        } else if retained < Updates::history() {
+            retained += 1;
+            true
+        } else {
+            expired.push(entry.hash.clone());
+            false
+        }
+    });
+    for hash in expired {
+        remove_files(&hash)?;
+    }
+
+    store(&entries)
+}
+
+/// Locates a history entry by its position (`0` is the most recently
+/// applied package, `1` the previous one) or by a unique hash prefix.
+pub fn find(selector: &str) -> Result<HistoryEntry> {
+    let entries = entries()?;
+    let selector = selector.trim();
+
+    if let Ok(n) = selector.parse::<usize>() {
+        if selector.len() < 8 {
+            return entries.get(n).cloned().ok_or_else(|| {
+                Error::custom(format!(
+                    "History entry `{n}` not found ({} entries)",
+                    entries.len()
+                ))
+            });
+        }
+    }
+
+    let selector = selector.to_lowercase();
+    let mut matches = entries
+        .into_iter()
+        .filter(|entry| entry.hash.starts_with(&selector));
+    match (matches.next(), matches.next()) {
+        (Some(entry), None) => Ok(entry),
+        (Some(_), Some(_)) => Err(Error::custom(format!(
+            "Package hash `{selector}` is ambiguous"
+        ))),
+        (None, _) => Err(Error::custom(format!(
+            "Package `{selector}` not found in history"
+        ))),
+    }
+}
+
+/// Loads a package from the history, verifying its signature.
+pub fn package(hash: &str) -> Result<Package> {
+    let entry = find(hash)?;
+    let data_path = data_file(&entry.hash)?;
+    let data = std::fs::read(&data_path).map_err(|err| Error::file(&data_path, err))?;
+    let signature_path = signature_file(&entry.hash)?;
+    let signature = if signature_path.exists() {
+        Some(std::fs::read(&signature_path).map_err(|err| Error::file(&signature_path, err))?)
+    } else {
+        None
+    };
+    let publisher = signing::verify(&data, signature.as_deref())?;
+
+    Ok(Package {
+        data,
+        signature,
+        publisher,
+        hash: entry.hash,
+        source: entry.source,
+    })
+}
+
+pub fn pinned() -> Result<Option<String>> {
+    let path = pin_file()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let hash = std::fs::read_to_string(&path).map_err(|err| Error::file(&path, err))?;
+    Ok(Some(hash.trim().to_string()).filter(|hash| !hash.is_empty()))
+}
+
+/// Pins the package matching the selector (see [`find`]).
+pub fn pin(selector: &str) -> Result<HistoryEntry> {
+    let entry = find(selector)?;
+    let path = pin_file()?;
+    std::fs::write(&path, &entry.hash).map_err(|err| Error::file(&path, err))?;
+    log_warn!("History", "Pinned package `{}`", entry.hash);
+    Ok(entry)
+}
+
+pub fn unpin() -> Result<()> {
+    let path = pin_file()?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|err| Error::file(&path, err))?;
+        log_success!("History", "Package pin cleared");
+    }
+    Ok(())
+}
+
+/// Lists the history (`resolver history`).
+pub fn list() -> Result<()> {
+    let History { pinned, entries } = history()?;
+    if entries.is_empty() {
+        log::info("No config history")?;
+    }
+    for (n, entry) in entries.iter().enumerate() {
+        let pin = if pinned.as_deref() == Some(entry.hash.as_str()) {
+            " [pinned]"
+        } else {
+            ""
+        };
+        log::info(format!("{n}: {entry}{pin}"))?;
+    }
+    Ok(())
+}
+
+/// Reinstalls a package from the history and pins it so that
+/// auto-update does not move past it (`resolver rollback`).
+pub fn rollback(selector: &str) -> Result<()> {
+    let package = package(selector)?;
+    let toml = package::decrypt(&package.data)?;
+    let nodes = Config::try_parse(toml.as_str())?;
+
+    let installed = load_config().unwrap_or_default();
+    let diff = NodeDiff::new(&installed, &nodes);
+    diff.render();
+    diff.validate()?;
+
+    install_package(&package)?;
+    pin(&package.hash)?;
+    log::success(format!(
+        "Rolled back to package `{}` ({} nodes)",
+        package.hash,
+        nodes.len()
+    ))?;
+    log::info("Updates are pinned to this package, use `history --unpin` to resume")?;
+    Ok(())
+}
@@ -1,26 +1,44 @@
+pub(crate) use crate::admin;
 pub use crate::args::Args;
 pub use crate::cache::NoCacheHtml;
+pub use crate::compose::*;
 pub use crate::config::*;
 pub use crate::connection::{Connection, Output};
+pub use crate::credentials::Credentials;
 pub use crate::delegate::*;
+pub use crate::diff::NodeDiff;
+pub use crate::discovery::{Discovery, DiscoverySource};
+pub use crate::drain::*;
+pub(crate) use crate::election;
+pub use crate::election::Reason;
 pub use crate::error::Error;
 pub use crate::events::Events;
+pub(crate) use crate::export;
 pub use crate::group::*;
+pub(crate) use crate::history;
 pub use crate::log::*;
 pub use crate::monitor::Monitor;
 pub use crate::node::*;
+pub(crate) use crate::package;
 pub use crate::params::PathParams;
 pub use crate::path::*;
 pub(crate) use crate::public;
+pub use crate::registry::*;
 pub use crate::resolver::Resolver;
 pub use crate::result::Result;
 pub(crate) use crate::rpc;
 pub use crate::rpc::ClientT;
-pub use crate::rpc::{Caps, Connections};
+pub use crate::rpc::{Caps, Connections, Metrics};
+pub(crate) use crate::schema;
 pub use crate::services::Service;
 pub(crate) use crate::session::*;
+pub(crate) use crate::settings;
+pub(crate) use crate::signing;
+pub(crate) use crate::sources;
+pub use crate::sources::UpdateSource;
+pub(crate) use crate::stats::*;
 pub(crate) use crate::status;
-pub use crate::tpl::Tpl;
+pub use crate::tpl::{Template, Tpl};
 pub use crate::transport::*;
 pub use crate::utils::*;
 
@@ -39,6 +57,7 @@ pub use futures::{select, FutureExt, StreamExt};
 pub use kaspa_consensus_core::network::{NetworkId, NetworkType};
 pub use kaspa_utils::hex::*;
 pub use rand::Rng;
+pub use schemars::JsonSchema;
 pub use serde::{de::DeserializeOwned, Deserialize, Serialize};
 pub use serde_hex::{SerHex, Strict};
 pub use std::collections::{HashMap, HashSet};
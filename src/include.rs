@@ -0,0 +1,206 @@
+//!
+//! Config file includes and `conf.d` directory merging.
+//!
+//! A config file can include additional files using glob patterns
+//! relative to its own location (`include = ["providers/*.toml"]`).
+//! Files located in the `conf.d` directory next to the main config
+//! file are merged after it in lexical order.
+//!
+
+use crate::imports::*;
+use std::ops::Range;
+
+/// Config file contents along with the file path
+/// used to report the origin of config entries.
+pub struct ConfigFile {
+    pub path: PathBuf,
+    pub toml: String,
+}
+
+impl ConfigFile {
+    pub fn new(path: impl Into<PathBuf>, toml: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            toml: toml.into(),
+        }
+    }
+
+    /// `<file>:<line>` of the config entry span.
+    pub fn origin(&self, span: Range<usize>) -> String {
+        let offset = span.start.min(self.toml.len());
+        let line = self.toml[..offset].matches('\n').count() + 1;
+        format!("{}:{line}", self.path.display())
+    }
+}
+
+#[derive(Deserialize)]
+struct Includes {
+    include: Option<Vec<String>>,
+}
+
+/// Loads the config file followed by the files it includes
+/// and files located in the adjacent `conf.d` directory.
+pub fn load(path: &Path) -> Result<Vec<ConfigFile>> {
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    load_file(path, &mut files, &mut visited)?;
+
+    if let Some(conf_d) = path.parent().map(|folder| folder.join("conf.d")) {
+        if conf_d.is_dir() {
+            for path in glob(&conf_d, "*.toml")? {
+                load_file(&path, &mut files, &mut visited)?;
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+fn load_file(
+    path: &Path,
+    files: &mut Vec<ConfigFile>,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<()> {
+    let canonical = path.canonicalize().map_err(|err| Error::file(path, err))?;
+    if !visited.insert(canonical) {
+        // already loaded (or circular include)
+        return Ok(());
+    }
+
+    let toml = std::fs::read_to_string(path).map_err(|err| Error::file(path, err))?;
+    let Includes { include } = toml::from_str::<Includes>(&toml)
+        .map_err(|err| Error::config(format!("{}: {err}", path.display())))?;
+    files.push(ConfigFile::new(path, toml));
+
+    let folder = path.parent().unwrap_or(Path::new("."));
+    for pattern in include.iter().flatten() {
+        let paths = glob(folder, pattern)?;
+        if paths.is_empty() && !pattern.contains(['*', '?', '[']) {
+            return Err(Error::config(format!(
+                "{}: included file `{pattern}` not found",
+                path.display()
+            )));
+        }
+        for path in paths {
+            load_file(&path, files, visited)?;
+        }
+    }
+
+    Ok(())
+}
+
+// files matching the pattern relative to the folder, in lexical order
+fn glob(folder: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let pattern = folder.join(pattern);
+    let pattern = pattern.to_string_lossy();
+    let mut paths = glob::glob(&pattern)
+        .map_err(|err| Error::config(format!("Invalid include pattern `{pattern}`: {err}")))?
+        .filter_map(|entry| entry.ok())
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // scratch folder removed when dropped
+    struct Folder(PathBuf);
+
+    impl Folder {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("resolver-include-{name}-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn write(&self, file: &str, toml: &str) -> PathBuf {
+            let path = self.0.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, toml).unwrap();
+            path
+        }
+    }
+
+    impl Drop for Folder {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn names(files: &[ConfigFile], folder: &Folder) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| {
+                file.path
+                    .strip_prefix(&folder.0)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn load_includes_and_conf_d_in_order() {
+        let folder = Folder::new("order");
+        let main = folder.write("resolver.toml", r#"include = ["providers/*.toml"]"#);
+        folder.write("providers/b.toml", "");
+        folder.write("providers/a.toml", "");
+        folder.write("conf.d/20-late.toml", "");
+        folder.write("conf.d/10-early.toml", "");
+        folder.write("conf.d/ignored.txt", "");
+
+        let files = load(&main).unwrap();
+        assert_eq!(
+            names(&files, &folder),
+            [
+                "resolver.toml",
+                "providers/a.toml",
+                "providers/b.toml",
+                "conf.d/10-early.toml",
+                "conf.d/20-late.toml",
+            ]
+        );
+    }
+
+    #[test]
+    fn load_skips_circular_includes() {
+        let folder = Folder::new("cycle");
+        let main = folder.write("resolver.toml", r#"include = ["a.toml"]"#);
+        folder.write("a.toml", r#"include = ["b.toml"]"#);
+        folder.write("b.toml", r#"include = ["a.toml", "resolver.toml"]"#);
+        // conf.d entries already included are loaded once
+        folder.write("conf.d/c.toml", r#"include = ["../a.toml"]"#);
+
+        let files = load(&main).unwrap();
+        assert_eq!(
+            names(&files, &folder),
+            ["resolver.toml", "a.toml", "b.toml", "conf.d/c.toml"]
+        );
+    }
+
+    #[test]
+    fn load_rejects_missing_includes() {
+        let folder = Folder::new("missing");
+        let main = folder.write("resolver.toml", r#"include = ["missing.toml"]"#);
+        let err = load(&main).err().expect("missing include");
+        assert!(err
+            .to_string()
+            .contains("included file `missing.toml` not found"));
+
+        // patterns matching no files are not errors
+        let main = folder.write("resolver.toml", r#"include = ["providers/*.toml"]"#);
+        assert_eq!(load(&main).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn origin_reports_line() {
+        let file = ConfigFile::new("resolver.toml", "a = 1\n\n[[node]]\n");
+        assert_eq!(file.origin(7..15), "resolver.toml:3");
+    }
+}
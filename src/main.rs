@@ -1,24 +1,42 @@
+mod admin;
 mod args;
 mod cache;
+mod compose;
 mod config;
 mod connection;
+mod credentials;
 mod delegate;
+mod diff;
+mod discovery;
+mod drain;
+mod election;
 mod error;
 mod events;
+mod export;
 mod group;
+mod history;
 mod imports;
+mod include;
 mod log;
+mod migrate;
 mod monitor;
 mod node;
+mod package;
 mod panic;
 mod params;
 mod path;
 mod public;
+mod registry;
 mod resolver;
 mod result;
 mod rpc;
+mod schema;
 mod services;
 mod session;
+mod settings;
+mod signing;
+mod sources;
+mod stats;
 mod status;
 mod tpl;
 mod transport;
@@ -52,7 +70,7 @@ async fn main() {
 async fn run() -> Result<()> {
     let args = Arc::new(Args::parse());
 
-    config::init(&args.user_config)?;
+    config::init(&args)?;
 
     match args.action {
         Action::Test => {
@@ -63,11 +81,39 @@ async fn run() -> Result<()> {
                 }
             }
         }
+        Action::Export { format } => {
+            export::export(format)?;
+        }
         Action::Login => {
             println!();
             config::generate_key()?;
             println!();
         }
+        Action::Publisher => {
+            println!();
+            signing::generate_publisher_key()?;
+            println!();
+        }
+        Action::Identity => {
+            println!();
+            package::generate_identity()?;
+            println!();
+        }
+        Action::AddRecipient { ref key, ref name } => {
+            println!();
+            package::add_recipient(key, name.clone())?;
+            println!();
+        }
+        Action::RemoveRecipient { ref key } => {
+            println!();
+            package::remove_recipient(key)?;
+            println!();
+        }
+        Action::Recipients => {
+            println!();
+            package::list_recipients()?;
+            println!();
+        }
         Action::Pack => {
             println!();
             config::pack()?;
@@ -78,11 +124,45 @@ async fn run() -> Result<()> {
             config::unpack()?;
             println!();
         }
-        Action::Update => {
+        Action::Migrate { from, to, force } => {
+            println!();
+            migrate::migrate(from, to, force)?;
+            println!();
+        }
+        Action::Update { dry_run } => {
+            println!();
+            config::update_global_config(dry_run).await?;
+            println!();
+        }
+        Action::History { unpin } => {
+            println!();
+            if unpin {
+                history::unpin()?;
+            }
+            history::list()?;
+            println!();
+        }
+        Action::Rollback { ref package } => {
             println!();
-            config::update_global_config().await?;
+            history::rollback(package)?;
             println!();
         }
+        Action::Reload => {
+            println!();
+            admin::reload(args.listen.as_str()).await?;
+            println!();
+        }
+        Action::Drain { target, drain } => {
+            println!();
+            admin::drain(args.listen.as_str(), target, drain).await?;
+            println!();
+        }
+        Action::Settings => {
+            settings::print()?;
+        }
+        Action::Schema => {
+            schema::print()?;
+        }
         Action::Run => {
             if let Err(err) = try_set_fd_limit(Limits::fd()) {
                 log_error!("FD Limit", "{err}");
@@ -0,0 +1,131 @@
+//!
+//! Config package schema migration (`resolver migrate --from <version>`).
+//!
+//! The package `resolver.<from>.bin` is decrypted and its TOML document
+//! is upgraded one version at a time using the transformation steps
+//! below. The result is validated against the current config schema
+//! and packed as `resolver.<to>.bin`. Steps only rewrite legacy entries,
+//! entries already in the target form are left unchanged. An existing
+//! target package is only replaced with `--force`.
+//!
+//! Steps are only defined for schema versions with a known format. The
+//! v1 and v2 schemas predate this tool and are not documented, so their
+//! packages are refused rather than upgraded by guesswork; they need to
+//! be re-created from their TOML source with `resolver pack`.
+//!
+
+use crate::imports::*;
+use toml::Table;
+
+struct Step {
+    // version the step upgrades from (to `from + 1`)
+    from: u64,
+    description: &'static str,
+    apply: fn(&mut Table) -> Result<()>,
+}
+
+// transformation steps, one per schema version
+const STEPS: &[Step] = &[];
+
+/// Upgrades a config document from schema version `from` to `to`.
+pub fn upgrade(toml: &str, from: u64, to: u64) -> Result<String> {
+    let mut config = toml::from_str::<Table>(toml)
+        .map_err(|err| Error::config(format!("Unable to parse config v{from}: {err}")))?;
+
+    for version in from..to {
+        let step = STEPS
+            .iter()
+            .find(|step| step.from == version)
+            .ok_or_else(|| {
+                Error::config(format!(
+                    "No migration step from v{version} to v{}: the v{version} schema is unknown, \
+                     re-create the package from its TOML source with `resolver pack`",
+                    version + 1
+                ))
+            })?;
+        log::info(format!(
+            "v{} -> v{}: {}",
+            step.from,
+            step.from + 1,
+            step.description
+        ))?;
+        (step.apply)(&mut config)
+            .map_err(|err| err.context(format!("Migration v{version} -> v{}", version + 1)))?;
+    }
+
+    toml::to_string(&config)
+        .map_err(|err| Error::config(format!("Unable to serialize config: {err}")))
+}
+
+/// Migrates the local `resolver.<from>.bin` package to `resolver.<to>.bin`.
+/// Refuses to replace an existing target package unless `force` is set.
+pub fn migrate(from: u64, to: u64, force: bool) -> Result<()> {
+    if from >= to {
+        return Err(Error::custom(format!(
+            "Invalid migration from v{from} to v{to}"
+        )));
+    }
+    if to != VERSION {
+        return Err(Error::custom(format!(
+            "Migration target must be the current config version v{VERSION}"
+        )));
+    }
+
+    let local_config_folder = local_config_folder().ok_or(Error::LocalConfigNotFound)?;
+    let target = local_config_folder.join(package_file(to));
+    if target.exists() && !force {
+        return Err(Error::custom(format!(
+            "Package `{}` already exists, use `--force` to overwrite",
+            target.display()
+        )));
+    }
+
+    let source = local_config_folder.join(package_file(from));
+    log::info(format!(
+        " in: {}\nout: {}",
+        source.display(),
+        target.display()
+    ))?;
+
+    let data = std::fs::read(&source).map_err(|err| Error::file(&source, err))?;
+    let toml = package::decrypt(&data)?;
+    let toml = upgrade(&toml, from, to)?;
+    let nodes = Config::try_parse(&toml)?;
+    log::success(format!("Migrated config contains {} nodes", nodes.len()))?;
+
+    write_package(&local_config_folder, to, &toml)?;
+    outro("Have a great day!")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[[node]]
+service = "kaspa"
+transport-type = "wrpc-borsh"
+tls = true
+network = "mainnet"
+fqdn = "n1.example.org"
+address = "wss://n1.example.org/kaspa/mainnet/wrpc/borsh"
+"#;
+
+    #[test]
+    fn upgrade_refuses_unknown_schemas() {
+        for version in 1..VERSION {
+            let err = upgrade(CONFIG, version, VERSION).unwrap_err().to_string();
+            assert!(
+                err.contains(&format!("v{version} schema is unknown")),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn upgrade_retains_current_documents() {
+        let toml = upgrade(CONFIG, VERSION, VERSION).unwrap();
+        assert_eq!(Config::try_parse(&toml).unwrap().len(), 1);
+    }
+}
@@ -7,10 +7,11 @@ pub struct Monitor {
     args: Arc<Args>,
     connections: RwLock<AHashMap<PathParams, Vec<Arc<Connection>>>>,
     delegates: RwLock<AHashMap<Delegate, Arc<Connection>>>,
-    sorts: AHashMap<PathParams, AtomicBool>,
+    sorts: RwLock<AHashMap<PathParams, AtomicBool>>,
     channel: Channel<PathParams>,
     shutdown_ctl: DuplexChannel<()>,
     service: Service,
+    drains: Arc<Drains>,
 }
 
 impl fmt::Debug for Monitor {
@@ -23,19 +24,16 @@ impl fmt::Debug for Monitor {
 }
 
 impl Monitor {
-    pub fn new(args: &Arc<Args>, service: Service) -> Self {
-        let sorts = PathParams::iter_tls_any()
-            .map(|params| (params, AtomicBool::new(false)))
-            .collect();
-
+    pub fn new(args: &Arc<Args>, service: Service, drains: &Arc<Drains>) -> Self {
         Self {
             args: args.clone(),
             connections: Default::default(),
             delegates: Default::default(),
-            sorts,
+            sorts: Default::default(),
             channel: Channel::unbounded(),
             shutdown_ctl: DuplexChannel::oneshot(),
             service,
+            drains: drains.clone(),
         }
     }
 
@@ -43,6 +41,10 @@ impl Monitor {
         self.args.verbose
     }
 
+    pub fn drains(&self) -> &Arc<Drains> {
+        &self.drains
+    }
+
     pub fn delegates(&self) -> &RwLock<AHashMap<Delegate, Arc<Connection>>> {
         &self.delegates
     }
@@ -52,13 +54,25 @@ impl Monitor {
     }
 
     pub fn to_vec(&self) -> Vec<Arc<Connection>> {
-        PathParams::iter_tls_strict()
-            .filter_map(|params| self.connections.read().unwrap().get(&params).cloned())
+        let connections = self.connections.read().unwrap();
+        sorted_params(&connections)
+            .into_iter()
+            .filter(PathParams::is_tls_strict)
+            .filter_map(|params| connections.get(&params).cloned())
             .flatten()
             .collect()
     }
 
     /// Process an update to `Server.toml` removing or adding node connections accordingly.
+    ///
+    /// The update is performed as a make-before-break reconciliation:
+    /// new connections are created and given time to come online
+    /// (see [`SyncSettings::reconcile`]), after which connection lists
+    /// are updated atomically and removed connections are stopped.
+    ///
+    /// The set of networks and transports is derived from the node
+    /// list (optionally restricted by [`ParamsSettings`]).
+    /// Connection lists and sort flags are rebuilt accordingly.
     pub async fn update_nodes(
         self: &Arc<Self>,
         global_node_list: &mut Vec<Arc<Node>>,
@@ -66,113 +80,121 @@ impl Monitor {
         let mut nodes = Vec::new();
         global_node_list.retain(|node| {
             if node.service() == self.service {
-                nodes.push(node.clone());
+                if ParamsSettings::accepts(node) {
+                    nodes.push(node.clone());
+                } else {
+                    log_warn!(
+                        "Update",
+                        "Ignoring node (network or transport not enabled in settings): {node}"
+                    );
+                }
                 false
             } else {
                 true
             }
         });
 
-        let mut connections = self.connections();
+        let connections = self.connections();
+
+        let mut created = Vec::new();
+        let mut removed = Vec::new();
 
-        let mut tls_any_created = Vec::new();
-        let mut tls_any_removed = Vec::new();
+        let params = nodes
+            .iter()
+            .map(|node| *node.params())
+            .chain(
+                connections
+                    .keys()
+                    .copied()
+                    .filter(PathParams::is_tls_strict),
+            )
+            .collect::<HashSet<_>>();
 
-        for params in PathParams::iter_tls_strict() {
+        for params in params {
             let nodes = nodes
                 .iter()
                 .filter(|node| node.params() == &params)
                 .collect::<Vec<_>>();
 
-            let list = connections.entry(params).or_default();
+            let list = connections.get(&params).cloned().unwrap_or_default();
 
-            let create: Vec<_> = nodes
-                .iter()
-                .filter(|node| !list.iter().any(|connection| connection.node() == **node))
-                .collect();
-
-            let remove: Vec<_> = list
-                .iter()
-                .filter(|connection| !nodes.iter().any(|node| connection.node() == *node))
-                .cloned()
-                .collect();
+            let (create, remove) = changes(&nodes, &list);
+            removed.extend(remove);
 
             for node in create {
-                let created = Arc::new(Connection::try_new(
+                let connection = Arc::new(Connection::try_new(
                     self.clone(),
                     (*node).clone(),
                     self.channel.sender.clone(),
                     &self.args,
                 )?);
-                created.start()?;
-                list.push(created.clone());
-                tls_any_created.push(created);
-            }
-
-            for removed in remove {
-                removed.stop().await?;
-                list.retain(|c| c.node() != removed.node());
-                tls_any_removed.push(removed);
+                connection.start()?;
+                created.push(connection);
             }
         }
 
-        // remove connections from TlsAny list
-        tls_any_removed.into_iter().for_each(|connection| {
-            let params = connection.params().to_tls(TlsKind::Any);
-            let list = connections.entry(params).or_default();
-            list.retain(|connection| connection.node() != connection.node());
-        });
-
-        // create connections in TlsAny list
-        tls_any_created.into_iter().for_each(|connection| {
-            let params = connection.params().to_tls(TlsKind::Any);
-            let list = connections.entry(params).or_default();
-            list.push(connection);
-        });
-
-        // collect all strict Tls connections and group them by network_uid (fqdn+network+tls)
-        let targets = AHashMap::group_from(
-            connections
-                .iter()
-                .filter_map(|(params, list)| params.is_tls_strict().then_some(list))
-                .flatten()
-                .map(|connection| {
-                    (
-                        connection.node().network_node_uid(),
-                        connection.node().transport_kind(),
-                        connection.clone(),
-                    )
-                }),
-        );
+        // make: give new connections a chance to come online
+        // before they become eligible for election
+        if !created.is_empty() {
+            let ts = Instant::now();
+            let timeout = SyncSettings::reconcile();
+            while ts.elapsed() < timeout && !created.iter().all(|c| c.is_online()) {
+                workflow_core::task::sleep(Duration::from_millis(250)).await;
+            }
 
-        for (_network_uid, transport_map) in targets.iter() {
-            if let Some(wrpc_borsh) = transport_map.get(&TransportKind::WrpcBorsh) {
-                if let Some(wrpc_json) = transport_map.get(&TransportKind::WrpcJson) {
-                    wrpc_json.bind_delegate(Some(wrpc_borsh.clone()));
-                } else if let Some(grpc) = transport_map.get(&TransportKind::Grpc) {
-                    grpc.bind_delegate(Some(wrpc_borsh.clone()));
-                }
+            let online = created.iter().filter(|c| c.is_online()).count();
+            if online < created.len() {
+                log_warn!(
+                    "Update",
+                    "{}: {} of {} new connection(s) online after {:1.2} sec",
+                    self.service,
+                    online,
+                    created.len(),
+                    ts.elapsed().as_secs_f64()
+                );
             }
         }
 
-        if self.args.debug {
-            for params in PathParams::iter_tls_any() {
-                println!("{}:{}", self.service, params);
-                if let Some(connections) = connections.get(&params) {
-                    if connections.is_empty() {
-                        println!("\t- None (0)");
-                    } else {
-                        for connection in connections {
-                            println!("\t- {}", connection);
+        let params = {
+            let mut connections = self.connections.write().unwrap();
+            reconcile(&mut connections, &created, &removed);
+            bind_delegates(&connections);
+
+            if self.args.debug {
+                for params in sorted_params(&connections) {
+                    println!("{}:{}", self.service, params);
+                    if let Some(connections) = connections.get(&params) {
+                        if connections.is_empty() {
+                            println!("\t- None (0)");
+                        } else {
+                            for connection in connections {
+                                println!("\t- {}", connection);
+                            }
                         }
+                    } else {
+                        println!("\t- N/A");
                     }
-                } else {
-                    println!("\t- N/A");
                 }
             }
+
+            connections.keys().copied().collect::<Vec<_>>()
+        };
+        // sort flags are updated after the connections lock is released
+        // (the monitor task acquires `sorts` before `connections`)
+        self.rebuild_sorts(&params);
+
+        for connection in created.iter() {
+            connection.update();
         }
 
-        *self.connections.write().unwrap() = connections;
+        // break: stop removed connections
+        let results =
+            futures::future::join_all(removed.iter().map(|connection| connection.stop())).await;
+        for (connection, result) in removed.iter().zip(results) {
+            if let Err(err) = result {
+                log_error!("Update", "Error stopping {}: {err}", connection.node());
+            }
+        }
 
         Ok(())
     }
@@ -207,11 +229,20 @@ impl Monitor {
             select! {
 
                 _ = interval.next().fuse() => {
-                    for (params, sort) in self.sorts.iter() {
-                        if sort.load(Ordering::Relaxed) {
-                            sort.store(false, Ordering::Relaxed);
-
-                            let mut connections = self.connections.write().unwrap();
+                    // collect flagged params and release `sorts` before
+                    // acquiring `connections` (see `update_nodes`)
+                    let flagged = self
+                        .sorts
+                        .read()
+                        .unwrap()
+                        .iter()
+                        .filter(|(_, sort)| sort.swap(false, Ordering::Relaxed))
+                        .map(|(params, _)| *params)
+                        .collect::<Vec<_>>();
+
+                    if !flagged.is_empty() {
+                        let mut connections = self.connections.write().unwrap();
+                        for params in flagged.iter() {
                             if let Some(nodes) = connections.get_mut(params) {
                                 nodes.sort_by_key(|connection| connection.score());
                             }
@@ -232,29 +263,41 @@ impl Monitor {
     }
 
     pub fn schedule_sort(&self, params_tls_kind: &PathParams) {
-        self.sorts
-            .get(params_tls_kind)
-            .unwrap()
-            .store(true, Ordering::Relaxed);
+        let sorts = self.sorts.read().unwrap();
+        for params in [*params_tls_kind, params_tls_kind.to_tls(TlsKind::Any)] {
+            if let Some(sort) = sorts.get(&params) {
+                sort.store(true, Ordering::Relaxed);
+            }
+        }
+    }
 
-        let params_tls_any = params_tls_kind.to_tls(TlsKind::Any);
-        self.sorts
-            .get(&params_tls_any)
-            .unwrap()
-            .store(true, Ordering::Relaxed);
+    // rebuild sort flags for the current set of connection lists;
+    // newly created lists are scheduled for sorting
+    fn rebuild_sorts(&self, params: &[PathParams]) {
+        let mut sorts = self.sorts.write().unwrap();
+        sorts.retain(|key, _| params.contains(key));
+        for params in params.iter() {
+            sorts
+                .entry(*params)
+                .or_insert_with(|| AtomicBool::new(true));
+        }
     }
 
     // /// Get JSON string representing node information (id, url, provider, link)
     pub fn election(&self, params: &PathParams) -> Option<String> {
-        if self.verbose() {
-            println!("election for: {}", params);
-        }
+        let connection = self.elect(params).ok()?;
+        serde_json::to_string(&Output::from(&connection)).ok()
+    }
 
+    /// Elects a node matching the params or reports the reason
+    /// no node could be elected.
+    pub fn elect(&self, params: &PathParams) -> std::result::Result<Arc<Connection>, Reason> {
         let connections = self.connections.read().unwrap();
 
         const DELEGATES_ONLY: bool = true;
 
         if self.verbose() {
+            log_info!("Election", "{params}");
             if let Some(connections) = connections.get(params) {
                 connections
                     .iter()
@@ -266,15 +309,22 @@ impl Monitor {
                         }
                     })
                     .for_each(|connection| {
-                        println!("\t- {}", connection);
+                        log_info!("Candidate", "{connection}");
                     });
             } else {
-                println!("\t- N/A");
+                log_info!("Candidate", "N/A");
             }
         }
 
-        let connections = connections
-            .get(params)?
+        let Some(list) = connections.get(params) else {
+            return if connections.keys().any(|p| p.network == params.network) {
+                Err(Reason::ConstraintUnsatisfied)
+            } else {
+                Err(Reason::UnknownNetwork)
+            };
+        };
+
+        let candidates = list
             .iter()
             .filter(|connection| {
                 if DELEGATES_ONLY {
@@ -283,29 +333,138 @@ impl Monitor {
                     connection.is_available()
                 }
             })
+            .filter(|connection| connection.node().weight() > 0.0 && !connection.is_draining())
             .collect::<Vec<_>>();
 
-        if !connections.is_empty() {
-            let node = select_with_weighted_rng(connections);
-            serde_json::to_string(&Output::from(node)).ok()
+        if !candidates.is_empty() {
+            Ok(select_with_weighted_rng(candidates).clone())
+        } else if list.iter().any(|connection| connection.is_ready()) {
+            // nodes are serviceable, but at capacity, draining or disabled
+            Err(Reason::NoCapacity)
         } else {
-            None
+            // nodes are offline, unsynced or pending capabilities
+            Err(Reason::AllOffline)
+        }
+    }
+}
+
+/// Applies created and removed connections to the connection lists,
+/// maintaining both strict Tls lists and the respective TlsAny lists.
+fn reconcile(
+    connections: &mut AHashMap<PathParams, Vec<Arc<Connection>>>,
+    created: &[Arc<Connection>],
+    removed: &[Arc<Connection>],
+) {
+    for connection in removed {
+        let params = connection.params();
+        for params in [params, params.to_tls(TlsKind::Any)] {
+            if let Some(list) = connections.get_mut(&params) {
+                list.retain(|c| c.node() != connection.node());
+            }
+        }
+    }
+
+    for connection in created {
+        let params = connection.params();
+        for params in [params, params.to_tls(TlsKind::Any)] {
+            let list = connections.entry(params).or_default();
+            if !list.iter().any(|c| c.node() == connection.node()) {
+                list.push(connection.clone());
+            }
+        }
+    }
+
+    // drop lists of networks and transports no longer configured
+    connections.retain(|_, list| !list.is_empty());
+}
+
+// connection list params in a stable (display) order
+fn sorted_params(connections: &AHashMap<PathParams, Vec<Arc<Connection>>>) -> Vec<PathParams> {
+    let mut params = connections.keys().copied().collect::<Vec<_>>();
+    params.sort_by_cached_key(|params| (params.network.to_string(), params.to_string()));
+    params
+}
+
+/// Nodes without a matching connection and connections without a
+/// matching node. Nodes that changed (see `Node::eq`) are reported
+/// on both sides, re-creating their connections.
+fn changes<'a>(
+    nodes: &[&'a Arc<Node>],
+    list: &[Arc<Connection>],
+) -> (Vec<&'a Arc<Node>>, Vec<Arc<Connection>>) {
+    let create = nodes
+        .iter()
+        .filter(|node| !list.iter().any(|connection| connection.node() == **node))
+        .copied()
+        .collect();
+    let remove = list
+        .iter()
+        .filter(|connection| !nodes.iter().any(|node| connection.node() == *node))
+        .cloned()
+        .collect();
+    (create, remove)
+}
+
+/// Binds delegates of all connections targeting the same node
+/// (fqdn+network+tls), where wRPC Borsh connection is the delegate.
+fn bind_delegates(connections: &AHashMap<PathParams, Vec<Arc<Connection>>>) {
+    // collect all strict Tls connections and group them by network_uid (fqdn+network+tls)
+    let targets = AHashMap::group_from(
+        connections
+            .iter()
+            .filter_map(|(params, list)| params.is_tls_strict().then_some(list))
+            .flatten()
+            .map(|connection| {
+                (
+                    connection.node().network_node_uid(),
+                    connection.node().transport_kind(),
+                    connection.clone(),
+                )
+            }),
+    );
+
+    for (_network_uid, transport_map) in targets.iter() {
+        let wrpc_borsh = transport_map.get(&TransportKind::WrpcBorsh);
+        // wRPC JSON (or gRPC if there is no wRPC JSON connection)
+        // delegates to the wRPC Borsh connection of the node
+        let delegator = transport_map
+            .get(&TransportKind::WrpcJson)
+            .or_else(|| transport_map.get(&TransportKind::Grpc));
+
+        // bindings are cleared for connections that have lost their delegate
+        for connection in transport_map.values() {
+            let delegate = match (wrpc_borsh, delegator) {
+                (Some(wrpc_borsh), Some(delegator)) if Arc::ptr_eq(connection, delegator) => {
+                    Some(wrpc_borsh.clone())
+                }
+                _ => None,
+            };
+            connection.bind_delegate(delegate);
         }
     }
 }
 
 fn select_with_weighted_rng(nodes: Vec<&Arc<Connection>>) -> &Arc<Connection> {
-    // Calculate total weight based on the position in the sorted list
-    let total_weight: usize = nodes.iter().enumerate().map(|(i, _)| nodes.len() - i).sum();
+    // Calculate node weights based on the position in the sorted list
+    // scaled by the configured node weight
+    let weights = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, connection)| (nodes.len() - i) as f64 * connection.node().weight())
+        .collect::<Vec<_>>();
+    let total_weight: f64 = weights.iter().sum();
+
+    if total_weight <= 0.0 {
+        return nodes[0];
+    }
 
     // Generate a random number within the range of total_weight
     let mut rng = rand::thread_rng();
-    let mut rand_weight = rng.gen_range(0..total_weight);
+    let mut rand_weight = rng.gen_range(0.0..total_weight);
 
     // Select a node based on the random weight
-    for (i, node) in nodes.iter().enumerate() {
-        let weight = nodes.len() - i;
-        if rand_weight < weight {
+    for (node, weight) in nodes.iter().zip(weights.iter()) {
+        if rand_weight < *weight {
             return node;
         }
         rand_weight -= weight;
@@ -314,3 +473,247 @@ fn select_with_weighted_rng(nodes: Vec<&Arc<Connection>>) -> &Arc<Connection> {
     // Fallback in case of error (shouldn't happen)
     nodes[0]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::Action;
+
+    fn args() -> Arc<Args> {
+        Arc::new(Args {
+            listen: "127.0.0.1:8888".to_string(),
+            rate_limit: None,
+            verbose: false,
+            trace: false,
+            debug: false,
+            auto_update: false,
+            user_config: None,
+            settings: None,
+            set: vec![],
+            public: false,
+            action: Action::Run,
+        })
+    }
+
+    fn monitor() -> Arc<Monitor> {
+        Arc::new(Monitor::new(
+            &args(),
+            Service::Kaspa,
+            &Arc::new(Drains::default()),
+        ))
+    }
+
+    fn connection(
+        monitor: &Arc<Monitor>,
+        fqdn: &str,
+        transport_kind: TransportKind,
+        tls: bool,
+    ) -> Arc<Connection> {
+        connection_with(
+            monitor,
+            node(fqdn, transport_kind, tls, NodeLimits::default()),
+        )
+    }
+
+    fn connection_with(monitor: &Arc<Monitor>, node: Arc<Node>) -> Arc<Connection> {
+        Arc::new(
+            Connection::try_new(
+                monitor.clone(),
+                node,
+                monitor.channel.sender.clone(),
+                &monitor.args,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn node(fqdn: &str, transport_kind: TransportKind, tls: bool, limits: NodeLimits) -> Arc<Node> {
+        let config = NodeConfig {
+            id: None,
+            service: Service::Kaspa,
+            address: None,
+            tls,
+            transport_kind,
+            network: NetworkId::new(NetworkType::Mainnet),
+            enable: None,
+            fqdn: fqdn.to_string(),
+            auth: None,
+            tags: None,
+            limits,
+        };
+        Arc::new(config.into())
+    }
+
+    fn len(connections: &AHashMap<PathParams, Vec<Arc<Connection>>>, params: PathParams) -> usize {
+        connections.get(&params).map(Vec::len).unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn reconcile_mirrors_strict_lists_in_tls_any() {
+        let monitor = monitor();
+        let a = connection(&monitor, "a.example.org", TransportKind::WrpcBorsh, true);
+        let b = connection(&monitor, "b.example.org", TransportKind::WrpcBorsh, false);
+        let any = a.params().to_tls(TlsKind::Any);
+        assert_eq!(any, b.params().to_tls(TlsKind::Any));
+
+        let mut connections = AHashMap::default();
+        reconcile(&mut connections, &[a.clone(), b.clone()], &[]);
+        assert_eq!(len(&connections, a.params()), 1);
+        assert_eq!(len(&connections, b.params()), 1);
+        assert_eq!(len(&connections, any), 2);
+
+        reconcile(&mut connections, &[], &[a.clone()]);
+        assert_eq!(len(&connections, a.params()), 0);
+        assert_eq!(len(&connections, b.params()), 1);
+        assert_eq!(len(&connections, any), 1);
+        assert!(Arc::ptr_eq(&connections[&any][0], &b));
+    }
+
+    #[tokio::test]
+    async fn reconcile_does_not_duplicate_tls_any_entries() {
+        let monitor = monitor();
+        let a = connection(&monitor, "a.example.org", TransportKind::WrpcBorsh, true);
+        let any = a.params().to_tls(TlsKind::Any);
+
+        let mut connections = AHashMap::default();
+        reconcile(&mut connections, &[a.clone()], &[]);
+        reconcile(&mut connections, &[a.clone()], &[]);
+        assert_eq!(len(&connections, a.params()), 1);
+        assert_eq!(len(&connections, any), 1);
+    }
+
+    #[tokio::test]
+    async fn reconcile_drops_empty_lists() {
+        let monitor = monitor();
+        let a = connection(&monitor, "a.example.org", TransportKind::WrpcJson, false);
+        let b = connection(&monitor, "b.example.org", TransportKind::WrpcBorsh, true);
+
+        let mut connections = AHashMap::default();
+        reconcile(&mut connections, &[a.clone(), b.clone()], &[]);
+        assert_eq!(connections.len(), 4);
+
+        reconcile(&mut connections, &[], &[a.clone()]);
+        assert_eq!(connections.len(), 2);
+        assert!(!connections.contains_key(&a.params()));
+        assert!(!connections.contains_key(&a.params().to_tls(TlsKind::Any)));
+
+        reconcile(&mut connections, &[], &[b.clone()]);
+        assert!(connections.is_empty());
+    }
+
+    #[tokio::test]
+    async fn bind_delegates_clears_removed_delegate() {
+        let monitor = monitor();
+        let borsh = connection(&monitor, "a.example.org", TransportKind::WrpcBorsh, true);
+        let json = connection(&monitor, "a.example.org", TransportKind::WrpcJson, true);
+
+        let mut connections = AHashMap::default();
+        reconcile(&mut connections, &[borsh.clone(), json.clone()], &[]);
+        bind_delegates(&connections);
+        assert!(borsh.is_delegate());
+        assert!(!json.is_delegate());
+        assert!(Arc::ptr_eq(&json.delegate(), &borsh));
+
+        reconcile(&mut connections, &[], &[borsh.clone()]);
+        bind_delegates(&connections);
+        assert!(json.is_delegate());
+        assert!(Arc::ptr_eq(&json.delegate(), &json));
+    }
+
+    #[tokio::test]
+    async fn changes_recreate_connections_with_changed_limits() {
+        let monitor = monitor();
+        let limits = NodeLimits::default();
+        let existing = connection_with(
+            &monitor,
+            node("a.example.org", TransportKind::WrpcBorsh, true, limits),
+        );
+        let list = vec![existing.clone()];
+
+        let unchanged = node("a.example.org", TransportKind::WrpcBorsh, true, limits);
+        let (create, remove) = changes(&[&unchanged], &list);
+        assert!(create.is_empty());
+        assert!(remove.is_empty());
+
+        for limits in [
+            NodeLimits {
+                weight: Some(2.0),
+                ..limits
+            },
+            NodeLimits {
+                max_clients: Some(100),
+                ..limits
+            },
+            NodeLimits {
+                fd_margin: Some(64),
+                ..limits
+            },
+            NodeLimits {
+                sockets_per_core: Some(1000),
+                ..limits
+            },
+        ] {
+            let changed = node("a.example.org", TransportKind::WrpcBorsh, true, limits);
+            assert_eq!(changed.uid(), existing.node().uid());
+            let (create, remove) = changes(&[&changed], &list);
+            assert_eq!(create.len(), 1);
+            assert!(Arc::ptr_eq(create[0], &changed));
+            assert_eq!(remove.len(), 1);
+            assert!(Arc::ptr_eq(&remove[0], &existing));
+        }
+    }
+
+    #[tokio::test]
+    async fn changes_recreate_connections_with_changed_tags() {
+        let monitor = monitor();
+        let existing = connection(&monitor, "a.example.org", TransportKind::WrpcBorsh, true);
+        let list = vec![existing.clone()];
+
+        let mut changed = (**existing.node()).clone();
+        changed.tags = vec!["eu".to_string()];
+        let changed = Arc::new(changed);
+        let (create, remove) = changes(&[&changed], &list);
+        assert_eq!(create.len(), 1);
+        assert_eq!(remove.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn election_v2_output_is_unchanged() {
+        let monitor = monitor();
+        let a = connection(&monitor, "a.example.org", TransportKind::WrpcBorsh, true);
+        let params = a.params();
+        {
+            let mut connections = monitor.connections.write().unwrap();
+            reconcile(&mut connections, &[a.clone()], &[]);
+            bind_delegates(&connections);
+        }
+
+        // not ready: v2 responds without a body, v3 reports the reason
+        assert_eq!(monitor.election(&params), None);
+        assert_eq!(monitor.elect(&params).err(), Some(Reason::AllOffline));
+
+        a.set_ready(Caps {
+            version: "1.0.0".to_string(),
+            system_id: 1,
+            git_hash: None,
+            total_memory: 0,
+            cpu_physical_cores: 1,
+            fd_limit: 1024,
+            clients_limit: 1024,
+            capacity: 1024,
+        });
+        let expected = format!(
+            r#"{{"uid":"{}","url":"{}"}}"#,
+            a.node().uid_as_str(),
+            a.address()
+        );
+        assert_eq!(monitor.election(&params), Some(expected.clone()));
+        assert_eq!(
+            monitor.election(&params.to_tls(TlsKind::Any)),
+            Some(expected)
+        );
+
+        let unknown = PathParams::new(TransportKind::WrpcJson, TlsKind::Any, params.network);
+        assert_eq!(monitor.election(&unknown), None);
+    }
+}
@@ -1,7 +1,49 @@
 use crate::imports::*;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Per-node capacity and election overrides. These can be
+/// specified on `[[node]]` and `[[group]]` entries.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct NodeLimits {
+    // election weight multiplier (default 1.0)
+    pub weight: Option<f64>,
+    // maximum number of clients (overrides cores * sockets-per-core)
+    pub max_clients: Option<u64>,
+    // number of file descriptors reserved for other node tasks
+    pub fd_margin: Option<u64>,
+    // number of client sockets per physical cpu core
+    pub sockets_per_core: Option<u32>,
+}
+
+impl NodeLimits {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(weight) = self.weight {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(Error::config(format!("Invalid weight: {weight}")));
+            }
+        }
+
+        if self.max_clients == Some(0) {
+            return Err(Error::config("`max-clients` must be greater than 0"));
+        }
+
+        if self.sockets_per_core == Some(0) {
+            return Err(Error::config("`sockets-per-core` must be greater than 0"));
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn weight(&self) -> f64 {
+        self.weight.unwrap_or(1.0)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct NodeConfig {
+    // stable node id (uid is derived from the address if absent)
+    pub id: Option<String>,
     // service type
     pub service: Service,
     // public URL for the node connection
@@ -12,47 +54,69 @@ pub struct NodeConfig {
     #[serde(rename = "transport-type")]
     pub transport_kind: TransportKind,
     // node network id
+    #[schemars(with = "String")]
     pub network: NetworkId,
     // entry is enabled
     pub enable: Option<bool>,
     // domain name (abc.example.com)
     pub fqdn: String,
+    // optional connection credentials
+    pub auth: Option<Credentials>,
+    // optional node tags
+    pub tags: Option<Vec<String>>,
+    // capacity and election overrides
+    #[serde(flatten)]
+    pub limits: NodeLimits,
 }
 
 impl From<NodeConfig> for Node {
     fn from(config: NodeConfig) -> Self {
         let NodeConfig {
+            id,
             service,
             address,
             tls,
             transport_kind,
             network,
             fqdn,
+            auth,
+            tags,
+            limits,
             ..
         } = config;
 
         let ws_proto = if tls { "wss://" } else { "ws://" };
 
         let address = address.unwrap_or_else(|| {
-            let transport = Transport {
-                kind: transport_kind,
-                tls,
-                template: format!(
-                    "{ws_proto}${{fqdn}}/${{service}}/${{network}}/${{protocol}}/${{encoding}}"
-                ),
-            };
-            transport.make_address(&fqdn, &service, &network)
+            format!(
+                "{ws_proto}{fqdn}/{service}/{network}/{}/{}",
+                transport_kind.protocol(),
+                transport_kind.encoding()
+            )
         });
 
         let tls = address.starts_with("wss://");
-        let uid = xxh3_64(address.as_bytes());
+        let address_uid = xxh3_64(address.as_bytes());
+        let uid = Node::make_uid(
+            id.as_deref(),
+            &address,
+            &service,
+            &network,
+            transport_kind,
+            tls,
+        );
         let uid_string = format!("{uid:016x}");
         let network_node_uid = xxh3_64(format!("{fqdn}{network}{tls}").as_bytes());
         let params = PathParams::new(transport_kind, tls.into(), network);
+        let credentials = auth.map(Arc::new);
 
         Self {
             uid,
             uid_string,
+            address_uid,
+            id,
+            group: None,
+            provenance: None,
             service,
             params,
             fqdn,
@@ -60,18 +124,42 @@ impl From<NodeConfig> for Node {
             transport_kind,
             network,
             network_node_uid,
+            credentials,
+            limits,
+            tags: tags.unwrap_or_default(),
         }
     }
 }
 
+/// Optional node properties (shared by group members).
+#[derive(Default, Clone, Debug)]
+pub struct NodeOptions {
+    pub id: Option<String>,
+    // fqdn pattern of the group the node originates from
+    pub group: Option<String>,
+    pub credentials: Option<Arc<Credentials>>,
+    pub limits: NodeLimits,
+    pub tags: Vec<String>,
+}
+
 // #[derive(Clone, Debug, Serialize, Deserialize)]
 #[derive(Clone, Debug)]
 pub struct Node {
-    // uid of the node connection (hash(address))
+    // uid of the node connection (hash(id+service+network+transport)
+    // if the node has an explicit id, otherwise hash(address))
     // #[serde(skip)]
     pub uid: u64,
     // #[serde(skip)]
     pub uid_string: String,
+    // address-derived uid of the node's current address
+    // (differs from `uid` for nodes with an explicit id)
+    pub address_uid: u64,
+    // explicit node id
+    pub id: Option<String>,
+    // fqdn pattern of the source group (`None` for `[[node]]` entries)
+    pub group: Option<String>,
+    // node source the node has been merged from
+    pub provenance: Option<Provenance>,
     // contains hash(fqdn+network_id)
     // #[serde(skip)]
     pub network_node_uid: u64,
@@ -91,13 +179,29 @@ pub struct Node {
     pub network: NetworkId,
     // domain name (abc.example.com)
     pub fqdn: String,
+    // connection credentials (never displayed)
+    pub credentials: Option<Arc<Credentials>>,
+    // capacity and election overrides
+    pub limits: NodeLimits,
+    // node tags
+    pub tags: Vec<String>,
 }
 
 impl Eq for Node {}
 
+// nodes with an explicit id retain their uid across address changes,
+// equality also covers the connection parameters and credentials so
+// that an updated node (or rotated credentials) replaces the existing
+// connection
 impl PartialEq for Node {
     fn eq(&self, other: &Self) -> bool {
         self.uid == other.uid
+            && self.address == other.address
+            && self.params == other.params
+            && self.credentials == other.credentials
+            && self.provenance == other.provenance
+            && self.limits == other.limits
+            && self.tags == other.tags
     }
 }
 
@@ -114,16 +218,25 @@ impl Node {
         transport: &Transport,
         fqdn: S1,
         address: S2,
+        options: NodeOptions,
     ) -> Arc<Self>
     where
         S1: Display,
         S2: Display,
     {
         let Transport { tls, kind, .. } = transport;
+        let NodeOptions {
+            id,
+            group,
+            credentials,
+            limits,
+            tags,
+        } = options;
 
         let address = address.to_string();
         let fqdn = fqdn.to_string();
-        let uid = xxh3_64(address.as_bytes());
+        let address_uid = xxh3_64(address.as_bytes());
+        let uid = Node::make_uid(id.as_deref(), &address, service, &network, *kind, *tls);
         let uid_string = format!("{uid:016x}");
 
         let network_node_uid = xxh3_64(format!("{fqdn}{network}{tls}").as_bytes());
@@ -133,6 +246,10 @@ impl Node {
         let node = Self {
             uid,
             uid_string,
+            address_uid,
+            id,
+            group,
+            provenance: None,
             service: *service,
             params,
             fqdn,
@@ -140,11 +257,30 @@ impl Node {
             transport_kind: *kind,
             network,
             network_node_uid,
+            credentials,
+            limits,
+            tags,
         };
 
         Arc::new(node)
     }
 
+    /// Derives the node connection uid. Nodes with an explicit id
+    /// retain their uid (and drain state) across address changes.
+    fn make_uid(
+        id: Option<&str>,
+        address: &str,
+        service: &Service,
+        network: &NetworkId,
+        kind: TransportKind,
+        tls: bool,
+    ) -> u64 {
+        match id {
+            Some(id) => xxh3_64(format!("{id}:{service}:{network}:{kind}:{tls}").as_bytes()),
+            None => xxh3_64(address.as_bytes()),
+        }
+    }
+
     #[inline]
     pub fn params(&self) -> &PathParams {
         &self.params
@@ -165,6 +301,41 @@ impl Node {
         self.uid
     }
 
+    /// Uid derived from the current node address (the uid
+    /// the node would have without an explicit id).
+    #[inline]
+    pub fn address_uid(&self) -> u64 {
+        self.address_uid
+    }
+
+    #[inline]
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    #[inline]
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    #[inline]
+    pub fn provenance(&self) -> Option<Provenance> {
+        self.provenance
+    }
+
+    /// Node attributed to the node source (copied only
+    /// if attributed to a different source).
+    pub fn with_provenance(self: &Arc<Self>, provenance: Provenance) -> Arc<Self> {
+        if self.provenance == Some(provenance) {
+            self.clone()
+        } else {
+            Arc::new(Self {
+                provenance: Some(provenance),
+                ..(**self).clone()
+            })
+        }
+    }
+
     #[inline]
     pub fn transport_kind(&self) -> TransportKind {
         self.transport_kind
@@ -184,6 +355,27 @@ impl Node {
     pub fn address(&self) -> &str {
         self.address.as_str()
     }
+
+    #[inline]
+    pub fn credentials(&self) -> Option<&Arc<Credentials>> {
+        self.credentials.as_ref()
+    }
+
+    #[inline]
+    pub fn limits(&self) -> &NodeLimits {
+        &self.limits
+    }
+
+    #[inline]
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Election weight multiplier of this node.
+    #[inline]
+    pub fn weight(&self) -> f64 {
+        self.limits.weight()
+    }
 }
 
 impl AsRef<Node> for Node {
@@ -0,0 +1,254 @@
+//!
+//! Multi-recipient configuration packages.
+//!
+//! The configuration is encrypted once with a random data key.
+//! The data key is then wrapped (X25519 + ChaCha20-Poly1305) for
+//! each recipient public key and stored in the package header.
+//! Each operator or resolver instance unpacks the configuration
+//! using its own identity key (`~/.kaspa-resolver/.identity`).
+//!
+//! Packages without the envelope header are legacy packages
+//! encrypted with the shared symmetric key (see [`load_key`]).
+//!
+
+use crate::imports::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+const MAGIC: [u8; 4] = *b"KRPK";
+const ENVELOPE_VERSION: u16 = 1;
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct WrappedKey {
+    public_key: [u8; 32],
+    wrapped_key: Vec<u8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct Envelope {
+    magic: [u8; 4],
+    version: u16,
+    ephemeral: [u8; 32],
+    recipients: Vec<WrappedKey>,
+    payload: Vec<u8>,
+}
+
+fn identity_file() -> PathBuf {
+    global_config_folder().join(".identity")
+}
+
+fn recipients_file() -> Result<PathBuf> {
+    let local_config_folder = local_config_folder().ok_or(Error::LocalConfigNotFound)?;
+    Ok(local_config_folder.join("recipients.toml"))
+}
+
+pub fn load_identity() -> Result<StaticSecret> {
+    let path = identity_file();
+    if !path.exists() {
+        return Err(Error::KeyNotFound);
+    }
+    let bytes = std::fs::read(&path).map_err(|err| Error::file(&path, err))?;
+    let bytes: [u8; 32] = bytes.as_slice().try_into()?;
+    Ok(StaticSecret::from(bytes))
+}
+
+pub fn generate_identity() -> Result<()> {
+    let path = identity_file();
+    if path.exists() && !cliclack::confirm("Identity key already exists. Overwrite?").interact()? {
+        return Ok(());
+    }
+
+    let secret = StaticSecret::from(rand::thread_rng().gen::<[u8; 32]>());
+    signing::write_private(&path, &secret.to_bytes()).map_err(|err| Error::file(&path, err))?;
+    log::success(format!(
+        "Identity public key: {}",
+        PublicKey::from(&secret).to_bytes().to_vec().to_hex()
+    ))?;
+    log::info("Provide this key to the package maintainer to be added as a recipient")?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recipient {
+    pub name: Option<String>,
+    pub key: String,
+}
+
+impl Recipient {
+    fn public_key(&self) -> Result<PublicKey> {
+        let bytes = <[u8; 32]>::from_hex(self.key.trim())
+            .map_err(|_| Error::config(format!("Invalid recipient key: `{}`", self.key)))?;
+        Ok(PublicKey::from(bytes))
+    }
+}
+
+impl Display for Recipient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} ({name})", self.key),
+            None => write!(f, "{}", self.key),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Recipients {
+    #[serde(rename = "recipient", default)]
+    recipients: Vec<Recipient>,
+}
+
+pub fn load_recipients() -> Result<Vec<Recipient>> {
+    let path = recipients_file()?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let toml = std::fs::read_to_string(&path).map_err(|err| Error::file(&path, err))?;
+    let Recipients { recipients } = toml::from_str(&toml)?;
+    for recipient in recipients.iter() {
+        recipient.public_key()?;
+    }
+    Ok(recipients)
+}
+
+fn store_recipients(recipients: Vec<Recipient>) -> Result<()> {
+    let path = recipients_file()?;
+    let toml = toml::to_string(&Recipients { recipients })
+        .map_err(|err| Error::custom(format!("Unable to serialize recipients: {err}")))?;
+    std::fs::write(&path, toml).map_err(|err| Error::file(&path, err))
+}
+
+pub fn add_recipient(key: &str, name: Option<String>) -> Result<()> {
+    let recipient = Recipient {
+        name,
+        key: key.trim().to_lowercase(),
+    };
+    recipient.public_key()?;
+
+    let mut recipients = load_recipients()?;
+    if recipients.iter().any(|r| r.key == recipient.key) {
+        return Err(Error::custom(format!(
+            "Recipient `{}` already exists",
+            recipient.key
+        )));
+    }
+    log::success(format!("Adding recipient {recipient}"))?;
+    recipients.push(recipient);
+    store_recipients(recipients)?;
+    log::info("Run `pack` to re-encrypt the package for the updated recipient list")?;
+    Ok(())
+}
+
+/// Removes recipient matching the public key or name.
+pub fn remove_recipient(key_or_name: &str) -> Result<()> {
+    let mut recipients = load_recipients()?;
+    let len = recipients.len();
+    recipients.retain(|r| r.key != key_or_name && r.name.as_deref() != Some(key_or_name));
+    if recipients.len() == len {
+        return Err(Error::custom(format!(
+            "Recipient `{key_or_name}` not found"
+        )));
+    }
+    store_recipients(recipients)?;
+    log::success(format!("Removed recipient `{key_or_name}`"))?;
+    log::info("Run `pack` to re-encrypt the package for the updated recipient list")?;
+    Ok(())
+}
+
+pub fn list_recipients() -> Result<()> {
+    let recipients = load_recipients()?;
+    if recipients.is_empty() {
+        log::info("No recipients (packages use the shared key)")?;
+    } else {
+        for recipient in recipients {
+            log::info(format!("{recipient}"))?;
+        }
+    }
+    Ok(())
+}
+
+// derive key-encryption key for a recipient from the X25519 shared secret
+fn key_encryption_key(shared: &[u8; 32], ephemeral: &[u8; 32], recipient: &[u8; 32]) -> Secret {
+    let material = [shared.as_slice(), ephemeral, recipient].concat();
+    Secret::from(sha256(&material).as_slice().to_vec())
+}
+
+/// Encrypts the configuration for a list of recipients.
+pub fn encrypt(toml: &str, recipients: &[Recipient]) -> Result<Vec<u8>> {
+    let mut rng = rand::thread_rng();
+    let data_key = Secret::from(rng.gen::<[u8; 32]>().to_vec());
+    let ephemeral_secret = StaticSecret::from(rng.gen::<[u8; 32]>());
+    let ephemeral = PublicKey::from(&ephemeral_secret).to_bytes();
+
+    let payload = chacha20poly1305::encrypt_slice(toml.as_bytes(), &data_key)?;
+
+    let recipients = recipients
+        .iter()
+        .map(|recipient| {
+            let public_key = recipient.public_key()?;
+            let shared = ephemeral_secret.diffie_hellman(&public_key);
+            let kek = key_encryption_key(shared.as_bytes(), &ephemeral, public_key.as_bytes());
+            let wrapped_key = chacha20poly1305::encrypt_slice(data_key.as_slice(), &kek)?;
+            Ok(WrappedKey {
+                public_key: public_key.to_bytes(),
+                wrapped_key: AsRef::<[u8]>::as_ref(&wrapped_key).to_vec(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let envelope = Envelope {
+        magic: MAGIC,
+        version: ENVELOPE_VERSION,
+        ephemeral,
+        recipients,
+        payload: AsRef::<[u8]>::as_ref(&payload).to_vec(),
+    };
+
+    Ok(borsh::to_vec(&envelope)?)
+}
+
+#[inline]
+pub fn is_multi_recipient(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+/// Decrypts a configuration package using the local identity key
+/// (multi-recipient packages) or the shared key (legacy packages).
+pub fn decrypt(data: &[u8]) -> Result<String> {
+    if !is_multi_recipient(data) {
+        let key = load_key()?;
+        let toml = chacha20poly1305::decrypt_slice(data, &key)?;
+        return Ok(toml.as_str()?.to_string());
+    }
+
+    let envelope = Envelope::try_from_slice(data)?;
+    if envelope.version != ENVELOPE_VERSION {
+        return Err(Error::custom(format!(
+            "Unsupported package version: {}",
+            envelope.version
+        )));
+    }
+
+    let identity = load_identity()?;
+    let public_key = PublicKey::from(&identity);
+    let wrapped = envelope
+        .recipients
+        .iter()
+        .find(|recipient| &recipient.public_key == public_key.as_bytes())
+        .ok_or_else(|| {
+            Error::custom(format!(
+                "Package is not encrypted for identity `{}`",
+                public_key.to_bytes().to_vec().to_hex()
+            ))
+        })?;
+
+    let shared = identity.diffie_hellman(&PublicKey::from(envelope.ephemeral));
+    let kek = key_encryption_key(
+        shared.as_bytes(),
+        &envelope.ephemeral,
+        public_key.as_bytes(),
+    );
+    let data_key = chacha20poly1305::decrypt_slice(&wrapped.wrapped_key, &kek)?;
+    let data_key = Secret::from(data_key.as_slice().to_vec());
+    let toml = chacha20poly1305::decrypt_slice(&envelope.payload, &data_key)?;
+    Ok(toml.as_str()?.to_string())
+}
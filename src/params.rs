@@ -1,19 +1,5 @@
 use crate::imports::*;
 
-pub static NETWORKS: &[NetworkId] = &[
-    NetworkId::new(NetworkType::Mainnet),
-    NetworkId::with_suffix(NetworkType::Testnet, 10),
-    NetworkId::with_suffix(NetworkType::Testnet, 11),
-    // NetworkId::new(NetworkType::Devnet),
-    // NetworkId::new(NetworkType::Simnet),
-];
-
-pub static TRANSPORTS: &[TransportKind] = &[
-    TransportKind::WrpcBorsh,
-    TransportKind::WrpcJson,
-    // TransportKind::Grpc,
-];
-
 #[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct PathParams {
     pub protocol: ProtocolKind,
@@ -35,37 +21,6 @@ impl PathParams {
         }
     }
 
-    // iterates only TlsKind::Tls and TlsKind::None variants
-    pub fn iter_tls_strict() -> impl Iterator<Item = PathParams> {
-        NETWORKS.iter().flat_map(move |network_id| {
-            TRANSPORTS
-                .iter()
-                .map(move |transport_kind| {
-                    PathParams::new(*transport_kind, TlsKind::Tls, *network_id)
-                })
-                .chain(TRANSPORTS.iter().map(move |transport_kind| {
-                    PathParams::new(*transport_kind, TlsKind::None, *network_id)
-                }))
-        })
-    }
-
-    // iterates TlsKind::Tls, TlsKind::None, and TlsKind::Any variants
-    pub fn iter_tls_any() -> impl Iterator<Item = PathParams> {
-        NETWORKS.iter().flat_map(move |network_id| {
-            TRANSPORTS
-                .iter()
-                .map(move |transport_kind| {
-                    PathParams::new(*transport_kind, TlsKind::Tls, *network_id)
-                })
-                .chain(TRANSPORTS.iter().map(move |transport_kind| {
-                    PathParams::new(*transport_kind, TlsKind::None, *network_id)
-                }))
-                .chain(TRANSPORTS.iter().map(move |transport_kind| {
-                    PathParams::new(*transport_kind, TlsKind::Any, *network_id)
-                }))
-        })
-    }
-
     #[inline]
     pub fn protocol(&self) -> ProtocolKind {
         self.protocol
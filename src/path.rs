@@ -8,6 +8,9 @@ use axum::{
 // We define our own `Path` extractor that customizes the error from `axum::extract::Path`
 pub struct UrlPath<T>(pub T);
 
+/// Rejection of the [`UrlPath`] extractor.
+pub type UrlPathRejection = (StatusCode, axum::Json<PathError>);
+
 #[async_trait]
 impl<S, T> FromRequestParts<S> for UrlPath<T>
 where
@@ -15,7 +18,7 @@ where
     T: DeserializeOwned + Send,
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, axum::Json<PathError>);
+    type Rejection = UrlPathRejection;
 
     async fn from_request_parts(
         parts: &mut Parts,
@@ -102,6 +105,6 @@ where
 
 #[derive(Serialize)]
 pub struct PathError {
-    message: String,
-    location: Option<String>,
+    pub(crate) message: String,
+    pub(crate) location: Option<String>,
 }
@@ -0,0 +1,398 @@
+//!
+//! Node self-registration. Node operators authenticated with a token
+//! issued by the resolver admin register nodes via `POST /register`.
+//! Registered nodes are probed and kept pending until approved by
+//! the admin, after which they are monitored alongside config nodes.
+//! Operators and registrations are persisted in
+//! `~/.kaspa-resolver/registry.json`.
+//!
+
+use crate::imports::*;
+use axum::http::StatusCode;
+use std::time::SystemTime;
+use tokio::net::TcpStream;
+
+// timeout of the registration reachability probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RegistrationStatus {
+    Pending,
+    Approved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RegistrationRequest {
+    pub service: Service,
+    pub network: NetworkId,
+    pub transport_type: TransportKind,
+    pub address: String,
+    // must match the address scheme if specified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Registration {
+    pub id: String,
+    pub operator: String,
+    #[serde(flatten)]
+    pub request: RegistrationRequest,
+    pub status: RegistrationStatus,
+    // unix timestamp (seconds) of the registration
+    pub ts: u64,
+}
+
+impl Registration {
+    fn node(&self) -> Result<Arc<Node>> {
+        let RegistrationRequest {
+            service,
+            network,
+            transport_type,
+            address,
+            ..
+        } = &self.request;
+        let (host, _) = parse_address(address)?;
+        let config = NodeConfig {
+            id: Some(format!("registry:{}", self.id)),
+            service: *service,
+            address: Some(address.clone()),
+            tls: address.starts_with("wss://"),
+            transport_kind: *transport_type,
+            network: *network,
+            enable: None,
+            fqdn: host.to_string(),
+            auth: None,
+            tags: Some(vec![
+                "registered".to_string(),
+                format!("operator:{}", self.operator),
+            ]),
+            limits: NodeLimits::default(),
+        };
+        Ok(Arc::new(config.into()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operator {
+    pub name: String,
+    // hex-encoded sha256 of the operator token
+    token: String,
+    pub ts: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryData {
+    operators: Vec<Operator>,
+    registrations: Vec<Registration>,
+}
+
+impl RegistryData {
+    // rejects duplicate addresses and registrations
+    // exceeding the per-operator limit
+    fn check(&self, operator: &str, address: &str) -> Result<()> {
+        if self
+            .registrations
+            .iter()
+            .any(|entry| entry.request.address == address)
+        {
+            return Err(Error::Http(
+                StatusCode::CONFLICT,
+                "Node address is already registered",
+            ));
+        }
+        let count = self
+            .registrations
+            .iter()
+            .filter(|entry| entry.operator == operator)
+            .count();
+        if count >= RegistrySettings::max_per_operator() {
+            return Err(Error::Http(
+                StatusCode::TOO_MANY_REQUESTS,
+                "Registration limit reached",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Registration store.
+#[derive(Debug, Default)]
+pub struct Registry {
+    data: RwLock<RegistryData>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn token_hash(token: &str) -> String {
+    sha256(token.trim().as_bytes()).as_slice().to_vec().to_hex()
+}
+
+/// Splits a `ws://` or `wss://` address into the host name
+/// and the `host:port` socket address used for probing.
+fn parse_address(address: &str) -> Result<(&str, String)> {
+    let invalid = || Error::Http(StatusCode::BAD_REQUEST, "Invalid node address");
+    let (rest, port) = if let Some(rest) = address.strip_prefix("wss://") {
+        (rest, 443)
+    } else if let Some(rest) = address.strip_prefix("ws://") {
+        (rest, 80)
+    } else {
+        return Err(invalid());
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if authority.is_empty() || authority.contains('@') {
+        return Err(invalid());
+    }
+
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => {
+            (host, port.parse::<u16>().map_err(|_| invalid())?)
+        }
+        _ => (authority, port),
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    Ok((host, format!("{host}:{port}")))
+}
+
+// path segments identifying the protocol and encoding of an address
+const PROTOCOLS: &[&str] = &["wrpc", "grpc"];
+const ENCODINGS: &[&str] = &["borsh", "json", "protobuf"];
+
+/// Rejects transports the resolver is unable to connect to and
+/// addresses whose scheme or path contradict the declared transport.
+fn validate_request(request: &RegistrationRequest) -> Result<()> {
+    let RegistrationRequest {
+        transport_type,
+        address,
+        tls,
+        ..
+    } = request;
+
+    if !transport_type.is_supported() {
+        return Err(Error::Http(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Unsupported transport type",
+        ));
+    }
+
+    if tls.is_some_and(|tls| tls != address.starts_with("wss://")) {
+        return Err(Error::Http(
+            StatusCode::BAD_REQUEST,
+            "Node address scheme does not match `tls`",
+        ));
+    }
+
+    let protocol = transport_type.protocol().to_string();
+    let encoding = transport_type.encoding().to_string();
+    let path = address
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or_default()
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default();
+    let mismatch = path.split('/').skip(1).any(|segment| {
+        (PROTOCOLS.contains(&segment) && segment != protocol)
+            || (ENCODINGS.contains(&segment) && segment != encoding)
+    });
+    if mismatch {
+        return Err(Error::Http(
+            StatusCode::BAD_REQUEST,
+            "Node address path does not match the transport type",
+        ));
+    }
+
+    Ok(())
+}
+
+impl Registry {
+    fn path() -> PathBuf {
+        global_config_folder().join("registry.json")
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        let data = if path.exists() {
+            let json = std::fs::read_to_string(&path).map_err(|err| Error::file(&path, err))?;
+            serde_json::from_str::<RegistryData>(&json)?
+        } else {
+            RegistryData::default()
+        };
+
+        Ok(Self {
+            data: RwLock::new(data),
+        })
+    }
+
+    fn store(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&*self.data.read().unwrap())?;
+        let path = Self::path();
+        std::fs::write(&path, json).map_err(|err| Error::file(&path, err))
+    }
+
+    /// Issues a new token for the operator (replacing the previous
+    /// token if the operator exists). The token is returned only once.
+    pub fn issue_token(&self, operator: &str) -> Result<String> {
+        let operator = operator.trim();
+        if operator.is_empty() {
+            return Err(Error::Http(
+                StatusCode::BAD_REQUEST,
+                "Invalid operator name",
+            ));
+        }
+        let token = rand::thread_rng().gen::<[u8; 32]>().to_vec().to_hex();
+        {
+            let mut data = self.data.write().unwrap();
+            data.operators.retain(|entry| entry.name != operator);
+            data.operators.push(Operator {
+                name: operator.to_string(),
+                token: token_hash(&token),
+                ts: now(),
+            });
+        }
+        self.store()?;
+        log_success!("Registry", "Issued token for operator `{operator}`");
+        Ok(token)
+    }
+
+    /// Revokes the operator token and removes its registrations.
+    pub fn revoke(&self, operator: &str) -> Result<()> {
+        {
+            let mut data = self.data.write().unwrap();
+            data.operators.retain(|entry| entry.name != operator);
+            data.registrations
+                .retain(|entry| entry.operator != operator);
+        }
+        self.store()?;
+        log_warn!("Registry", "Revoked operator `{operator}`");
+        Ok(())
+    }
+
+    /// Operator authenticated by the token.
+    pub fn authenticate(&self, token: &str) -> Option<String> {
+        let hash = token_hash(token);
+        self.data
+            .read()
+            .unwrap()
+            .operators
+            .iter()
+            .find(|operator| operator.token == hash)
+            .map(|operator| operator.name.clone())
+    }
+
+    /// Probes the node and records a pending registration.
+    pub async fn register(
+        &self,
+        operator: &str,
+        request: RegistrationRequest,
+    ) -> Result<Registration> {
+        let (_, authority) = parse_address(&request.address)?;
+        validate_request(&request)?;
+        self.data
+            .read()
+            .unwrap()
+            .check(operator, &request.address)?;
+
+        match tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect(&authority)).await {
+            Ok(Ok(_)) => {}
+            _ => {
+                return Err(Error::Http(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "Node is not reachable",
+                ))
+            }
+        }
+
+        let registration = Registration {
+            id: uuid::Uuid::new_v4().to_string(),
+            operator: operator.to_string(),
+            request,
+            status: RegistrationStatus::Pending,
+            ts: now(),
+        };
+        // validate the resulting node
+        registration.node()?;
+
+        {
+            // re-check as concurrent registrations
+            // may have completed during the probe
+            let mut data = self.data.write().unwrap();
+            data.check(operator, &registration.request.address)?;
+            data.registrations.push(registration.clone());
+        }
+        self.store()?;
+        log_info!(
+            "Registry",
+            "Pending registration `{}` by `{operator}`: {}",
+            registration.id,
+            registration.request.address
+        );
+        Ok(registration)
+    }
+
+    /// Approves a pending registration or removes the registration
+    /// (`approve = false`).
+    pub fn approve(&self, id: &str, approve: bool) -> Result<()> {
+        {
+            let mut data = self.data.write().unwrap();
+            let index = data
+                .registrations
+                .iter()
+                .position(|entry| entry.id == id)
+                .ok_or(Error::Http(StatusCode::NOT_FOUND, "Registration not found"))?;
+            if approve {
+                data.registrations[index].status = RegistrationStatus::Approved;
+            } else {
+                data.registrations.remove(index);
+            }
+        }
+        self.store()?;
+        if approve {
+            log_success!("Registry", "Approved registration `{id}`");
+        } else {
+            log_warn!("Registry", "Removed registration `{id}`");
+        }
+        Ok(())
+    }
+
+    /// Nodes of approved registrations.
+    pub fn nodes(&self) -> Vec<Arc<Node>> {
+        self.data
+            .read()
+            .unwrap()
+            .registrations
+            .iter()
+            .filter(|entry| entry.status == RegistrationStatus::Approved)
+            .filter_map(|entry| match entry.node() {
+                Ok(node) => Some(node),
+                Err(err) => {
+                    log_error!("Registry", "Registration `{}`: {err}", entry.id);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        let data = self.data.read().unwrap();
+        let operators = data
+            .operators
+            .iter()
+            .map(|operator| serde_json::json!({ "name": operator.name, "ts": operator.ts }))
+            .collect::<Vec<_>>();
+        Ok(serde_json::to_string(&serde_json::json!({
+            "operators": operators,
+            "registrations": data.registrations,
+        }))?)
+    }
+}
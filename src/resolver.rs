@@ -23,18 +23,24 @@ struct Inner {
     shutdown_ctl: DuplexChannel<()>,
     events: Channel<Events>,
     sessions: Sessions,
+    drains: Arc<Drains>,
+    registry: Arc<Registry>,
+    composer: Composer,
 }
 
 impl Inner {
-    fn new(args: &Arc<Args>) -> Self {
+    fn new(args: &Arc<Args>, drains: Arc<Drains>, registry: Arc<Registry>) -> Self {
         Self {
             args: args.clone(),
             http_server: Default::default(),
-            kaspa: Arc::new(Monitor::new(args, Service::Kaspa)),
-            sparkle: Arc::new(Monitor::new(args, Service::Sparkle)),
+            kaspa: Arc::new(Monitor::new(args, Service::Kaspa, &drains)),
+            sparkle: Arc::new(Monitor::new(args, Service::Sparkle, &drains)),
             shutdown_ctl: DuplexChannel::oneshot(),
             events: Channel::unbounded(),
             sessions: Sessions::new(HttpStatus::sessions(), HttpStatus::ttl()),
+            drains,
+            composer: Composer::with_args(args, &registry),
+            registry,
         }
     }
 }
@@ -46,8 +52,10 @@ pub struct Resolver {
 
 impl Resolver {
     pub fn try_new(args: &Arc<Args>) -> Result<Self> {
+        let drains = Arc::new(Drains::load()?);
+        let registry = Arc::new(Registry::load()?);
         Ok(Self {
-            inner: Arc::new(Inner::new(args)),
+            inner: Arc::new(Inner::new(args, drains, registry)),
         })
     }
 
@@ -70,6 +78,18 @@ impl Resolver {
             get(|path| async move { this.get_elected_sparkle(path).await }),
         );
 
+        let this = self.clone();
+        router = router.route(
+            "/v3/kaspa/:network/:tls/:protocol/:encoding",
+            get(|path| async move { this.get_elected_v3(Service::Kaspa, path).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/v3/sparkle/:network/:tls/:protocol/:encoding",
+            get(|path| async move { this.get_elected_v3(Service::Sparkle, path).await }),
+        );
+
         let this = self.clone();
         router = router.route(
             "/status/logout",
@@ -98,6 +118,74 @@ impl Resolver {
             get(|req: Request<Body>| async move { status::json_handler(&this, req).await }),
         );
 
+        let this = self.clone();
+        router = router.route(
+            "/status/update",
+            get(|req: Request<Body>| async move { admin::update_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/status/config",
+            get(|req: Request<Body>| async move { admin::config_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/drain",
+            get(|req: Request<Body>| async move { admin::drain_list_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/history",
+            get(|req: Request<Body>| async move { admin::history_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/pin",
+            post(|req: Request<Body>| async move { admin::pin_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/reload",
+            post(|req: Request<Body>| async move { admin::reload_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/drain",
+            post(|req: Request<Body>| async move { admin::drain_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/registry",
+            get(|req: Request<Body>| async move { admin::registry_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/registry/approve",
+            post(|req: Request<Body>| async move { admin::approve_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/registry/token",
+            post(|req: Request<Body>| async move { admin::token_handler(&this, req).await }),
+        );
+
+        if RegistrySettings::enable() {
+            let this = self.clone();
+            router = router.route(
+                "/register",
+                post(|req: Request<Body>| async move { admin::register_handler(&this, req).await }),
+            );
+        }
+
         if self.args().public() {
             let this = self.clone();
             router = router.route(
@@ -174,6 +262,9 @@ impl Resolver {
             }
         });
 
+        #[cfg(unix)]
+        self.init_sighup_handler()?;
+
         self.inner.events.send(Events::Start).await?;
 
         Ok(())
@@ -192,6 +283,25 @@ impl Resolver {
         Ok(())
     }
 
+    /// Triggers configuration reload on `SIGHUP`.
+    #[cfg(unix)]
+    fn init_sighup_handler(self: &Arc<Self>) -> Result<()> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut sighup = signal(SignalKind::hangup())?;
+        let events = self.inner.events.sender.clone();
+        spawn(async move {
+            while sighup.recv().await.is_some() {
+                log_info!("Config", "SIGHUP received");
+                if events.send(Events::Reload).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(())
+    }
+
     async fn task(self: Arc<Self>) -> Result<()> {
         let events = self.inner.events.receiver.clone();
         let shutdown_ctl_receiver = self.inner.shutdown_ctl.request.receiver.clone();
@@ -199,6 +309,8 @@ impl Resolver {
 
         let mut sessions = workflow_core::task::interval(Duration::from_secs(3600));
         let mut update = workflow_core::task::interval(Updates::duration());
+        let mut discover = workflow_core::task::interval(DiscoverySettings::interval());
+        let mut watch = workflow_core::task::interval(Duration::from_secs(2));
 
         loop {
             select! {
@@ -206,17 +318,8 @@ impl Resolver {
                 msg = events.recv().fuse() => {
                     match msg {
                         Ok(event) => {
-                            match event {
-                                Events::Start => {
-                                    if let Err(err) = self.update(true).await {
-                                        log_error!("Config", "[startup] {err}");
-                                    }
-                                },
-                                Events::Update => {
-                                    if let Err(err) = self.update(false).await {
-                                        log_error!("Config", "[update] {err}");
-                                    }
-                                },
+                            if let Err(err) = self.update(&event).await {
+                                log_error!("Update", "[{event:?}] {err}");
                             }
                         }
                         Err(err) => {
@@ -235,6 +338,18 @@ impl Resolver {
                     self.inner.events.send(Events::Update).await?;
                 }
 
+                _ = discover.next().fuse() => {
+                    if !DiscoverySettings::sources().is_empty() {
+                        self.inner.events.send(Events::Discover).await?;
+                    }
+                }
+
+                _ = watch.next().fuse() => {
+                    if user_config_changed() {
+                        self.inner.events.send(Events::Reload).await?;
+                    }
+                }
+
                 _ = shutdown_ctl_receiver.recv().fuse() => {
                     break;
                 },
@@ -247,7 +362,17 @@ impl Resolver {
         Ok(())
     }
 
+    /// Polls node sources with the event and applies the
+    /// merged node list to monitors if any of the sources changed.
+    async fn update(self: &Arc<Self>, event: &Events) -> Result<()> {
+        if self.inner.composer.poll(event).await {
+            self.update_nodes(self.inner.composer.nodes()).await?;
+        }
+        Ok(())
+    }
+
     async fn update_nodes(self: &Arc<Self>, mut global_node_list: Vec<Arc<Node>>) -> Result<()> {
+        self.inner.drains.migrate(&global_node_list)?;
         self.inner.kaspa.update_nodes(&mut global_node_list).await?;
         self.inner
             .sparkle
@@ -260,36 +385,23 @@ impl Resolver {
         Ok(())
     }
 
-    async fn update(self: &Arc<Self>, first_update: bool) -> Result<()> {
-        if let Some(node_list) = user_config() {
-            // load user config
-            // occurs only during start
-            if first_update {
-                self.update_nodes(node_list).await?;
-            }
-            Ok(())
-        } else if self.args().auto_update {
-            // auto update global config
-            match update_global_config().await {
-                Ok(Some(global_node_list)) => {
-                    self.update_nodes(global_node_list).await?;
-                    Ok(())
-                }
-                Ok(None) => Ok(()),
-                Err(_) if first_update => {
-                    // fallback to local config on first update
-                    let node_list = load_config()?;
-                    self.update_nodes(node_list).await?;
-                    Ok(())
-                }
-                Err(err) => Err(err),
-            }
-        } else {
-            // no auto update, load local config
-            let node_list = load_config()?;
-            self.update_nodes(node_list).await?;
-            Ok(())
-        }
+    /// Schedule configuration update in the resolver task.
+    pub async fn schedule_update(&self) -> Result<()> {
+        self.inner.events.send(Events::Update).await?;
+        Ok(())
+    }
+
+    /// Schedule re-application of node sources (such as registered
+    /// nodes) in the resolver task, without reloading the config.
+    pub async fn schedule_refresh(&self) -> Result<()> {
+        self.inner.events.send(Events::Refresh).await?;
+        Ok(())
+    }
+
+    /// Schedule configuration reload in the resolver task.
+    pub async fn schedule_reload(&self) -> Result<()> {
+        self.inner.events.send(Events::Reload).await?;
+        Ok(())
     }
 
     // // respond with a JSON object containing the status of all nodes
@@ -333,9 +445,42 @@ impl Resolver {
         }
     }
 
+    // respond with a JSON envelope containing the elected node
+    // or the reason the election has failed
+    async fn get_elected_v3(
+        &self,
+        service: Service,
+        path: std::result::Result<UrlPath<PathParams>, UrlPathRejection>,
+    ) -> impl IntoResponse {
+        let params = match path {
+            Ok(UrlPath(params)) => params,
+            Err((_, axum::Json(error))) => return election::invalid_request(error),
+        };
+
+        let monitor = match service {
+            Service::Kaspa => &self.inner.kaspa,
+            Service::Sparkle => &self.inner.sparkle,
+        };
+        election::respond(monitor.elect(&params))
+    }
+
     pub fn sessions(&self) -> &Sessions {
         &self.inner.sessions
     }
+
+    pub fn drains(&self) -> &Arc<Drains> {
+        &self.inner.drains
+    }
+
+    pub fn registry(&self) -> &Arc<Registry> {
+        &self.inner.registry
+    }
+
+    /// Indicates if the address belongs to a configured
+    /// (not self-registered) node.
+    pub fn is_configured(&self, address: &str) -> bool {
+        self.inner.composer.is_configured(address)
+    }
 }
 
 #[inline]
@@ -1,7 +1,7 @@
-use super::{Caps, Connections};
+use super::{Caps, Connections, Metrics};
 use crate::imports::*;
 pub use kaspa_rpc_core::api::rpc::RpcApi;
-use kaspa_rpc_core::{GetConnectionsResponse, GetSystemInfoResponse};
+use kaspa_rpc_core::{GetConnectionsResponse, GetMetricsResponse, GetSystemInfoResponse};
 pub use kaspa_wrpc_client::KaspaRpcClient;
 
 // reduce fd_limit by this amount to ensure the
@@ -19,17 +19,35 @@ const FD_MARGIN: u64 = 1024;
 pub struct Client {
     client: KaspaRpcClient,
     url: String,
+    credentials: Option<Arc<Credentials>>,
+    limits: NodeLimits,
 }
 
 impl Client {
-    pub fn try_new(encoding: WrpcEncoding, url: &str) -> Result<Self> {
+    pub fn try_new(
+        encoding: WrpcEncoding,
+        url: &str,
+        credentials: Option<Arc<Credentials>>,
+        limits: NodeLimits,
+    ) -> Result<Self> {
         let client = KaspaRpcClient::new(encoding, Some(url), None, None, None)?;
 
         Ok(Self {
             client,
             url: url.to_string(),
+            credentials,
+            limits,
         })
     }
+
+    /// URL used to establish the connection, including
+    /// credentials. This URL must never be displayed.
+    fn connect_url(&self) -> String {
+        self.credentials
+            .as_ref()
+            .map(|credentials| credentials.connect_url(&self.url))
+            .unwrap_or_else(|| self.url.clone())
+    }
 }
 
 impl rpc::ClientT for Client {
@@ -41,7 +59,7 @@ impl rpc::ClientT for Client {
         let options = ConnectOptions {
             block_async_connect: false,
             strategy: ConnectStrategy::Retry,
-            url: Some(self.url.clone()),
+            url: Some(self.connect_url()),
             ..Default::default()
         };
 
@@ -69,16 +87,27 @@ impl rpc::ClientT for Client {
         } = self.client.get_system_info().await?;
         let cpu_physical_cores = cpu_physical_cores as u64;
         let fd_limit = fd_limit as u64;
-        // reduce node's fd_limit by FD_MARGIN to ensure
-        // the system has enough file descriptors for other
-        // tasks (peers, db, etc)
-        let fd_limit_actual = fd_limit.checked_sub(FD_MARGIN).unwrap_or(32);
+        // reduce node's fd_limit by FD_MARGIN (or the configured
+        // `fd-margin`) to ensure the system has enough file
+        // descriptors for other tasks (peers, db, etc)
+        let fd_margin = self.limits.fd_margin.unwrap_or(FD_MARGIN);
+        let fd_limit_actual = fd_limit.checked_sub(fd_margin).unwrap_or(32);
         // by default we assume that the node is able to accept
         // 1024 connections per core (default NGINX worker configuration)
         // TODO: this should be increased in the future once a custom
         // proxy is implemented
-        let clients_limit = cpu_physical_cores
-            * proxy_socket_limit_per_cpu_core.unwrap_or(rpc::SOCKETS_PER_CORE) as u64;
+        // configured `sockets-per-core` takes precedence over
+        // the value reported by the node
+        let sockets_per_core = self
+            .limits
+            .sockets_per_core
+            .or(proxy_socket_limit_per_cpu_core)
+            .unwrap_or(rpc::SOCKETS_PER_CORE);
+        // configured `max-clients` overrides the derived limit
+        let clients_limit = self
+            .limits
+            .max_clients
+            .unwrap_or(cpu_physical_cores * sockets_per_core as u64);
         let system_id = system_id
             .and_then(|v| v[0..8].try_into().ok().map(u64::from_be_bytes))
             .unwrap_or_default();
@@ -111,6 +140,25 @@ impl rpc::ClientT for Client {
         })
     }
 
+    async fn get_metrics(&self) -> Result<Metrics> {
+        let GetMetricsResponse {
+            process_metrics, ..
+        } = self
+            .client
+            .get_metrics(true, false, false, false, false, false)
+            .await?;
+        let process_metrics = process_metrics.ok_or(Error::Metrics)?;
+        // cpu usage is reported relative to a single core
+        let cores = process_metrics.core_num.max(1) as f64;
+        let cpu_usage = process_metrics.cpu_usage as f64 / cores;
+
+        Ok(Metrics {
+            cpu_usage,
+            resident_set_size: process_metrics.resident_set_size,
+            fd_num: process_metrics.fd_num as u64,
+        })
+    }
+
     fn trigger_abort(&self) -> Result<()> {
         Ok(self.client.trigger_abort()?)
     }
@@ -45,6 +45,37 @@ impl Caps {
     }
 }
 
+/// Node process resource usage (see [`ClientT::get_metrics`]).
+#[derive(Debug)]
+pub struct Metrics {
+    // process cpu usage normalized to the number of cores (0.0..=1.0)
+    pub cpu_usage: f64,
+    // process resident set size in bytes
+    pub resident_set_size: u64,
+    // number of file descriptors used by the process
+    pub fd_num: u64,
+}
+
+impl Metrics {
+    /// Resource pressure (0.0..=1.0) of the node process, determined
+    /// by the most constrained resource: cpu, memory or file descriptors.
+    /// Connected sockets are excluded from the file descriptor count as
+    /// they are already accounted for by the connection score.
+    pub fn pressure(&self, caps: &Caps, sockets: u64) -> f64 {
+        let memory = if caps.total_memory > 0 {
+            self.resident_set_size as f64 / caps.total_memory as f64
+        } else {
+            0.0
+        };
+        let fd = if caps.fd_limit > 0 {
+            self.fd_num.saturating_sub(sockets) as f64 / caps.fd_limit as f64
+        } else {
+            0.0
+        };
+        self.cpu_usage.max(memory).max(fd).clamp(0.0, 1.0)
+    }
+}
+
 #[derive(Debug)]
 pub struct Connections {
     pub clients: u64,
@@ -89,6 +120,12 @@ pub trait ClientT: std::fmt::Debug + Sized + Send + Sync + 'static {
         unimplemented!()
     }
 
+    /// Node process metrics; clients not exposing them fail with
+    /// [`Error::Metrics`], leaving the node score unaffected.
+    async fn get_metrics(&self) -> Result<Metrics> {
+        Err(Error::Metrics)
+    }
+
     fn trigger_abort(&self) -> Result<()> {
         unimplemented!()
     }
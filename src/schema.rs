@@ -0,0 +1,164 @@
+//!
+//! JSON Schema of the config format (`resolver schema`) and detection
+//! of unknown config and settings keys. Unknown keys are reported as
+//! warnings along with the closest known key; in strict mode
+//! (`config.strict`) they are rejected.
+//!
+
+use crate::imports::*;
+use serde_json::{Map, Value};
+use std::sync::LazyLock;
+
+// minimum similarity of a known key suggested for an unknown key
+const SUGGESTION_THRESHOLD: f64 = 0.7;
+
+/// JSON Schema of the config format. Tables do not accept
+/// keys other than the documented ones.
+static SCHEMA: LazyLock<Value> = LazyLock::new(|| strict(schemars::schema_for!(Config)));
+
+// JSON Schema of the runtime settings (`Resolver.toml`)
+static SETTINGS_SCHEMA: LazyLock<Value> = LazyLock::new(|| strict(schemars::schema_for!(Settings)));
+
+fn strict(schema: schemars::schema::RootSchema) -> Value {
+    let mut schema = serde_json::to_value(schema).expect("schema");
+    deny_additional_properties(&mut schema);
+    schema
+}
+
+// set `additionalProperties: false` on all objects with fixed properties
+fn deny_additional_properties(schema: &mut Value) {
+    match schema {
+        Value::Object(object) => {
+            if object.contains_key("properties") && !object.contains_key("additionalProperties") {
+                object.insert("additionalProperties".to_string(), Value::Bool(false));
+            }
+            object.values_mut().for_each(deny_additional_properties);
+        }
+        Value::Array(array) => array.iter_mut().for_each(deny_additional_properties),
+        _ => {}
+    }
+}
+
+/// Prints the config JSON Schema (`resolver schema`).
+pub fn print() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&*SCHEMA)?);
+    Ok(())
+}
+
+/// Unknown keys of a config document, one message per key such as
+/// ``unknown key `node[0].transport_type` (did you mean `transport-type`?)``.
+/// Documents that fail to parse are reported by the config parser.
+pub fn unknown_keys(toml: &str) -> Vec<String> {
+    let Ok(table) = toml::from_str::<toml::Table>(toml) else {
+        return vec![];
+    };
+    unknown(&SCHEMA, table)
+}
+
+/// Unknown keys of a settings table (see [`unknown_keys`]).
+pub fn unknown_settings(table: &toml::Table) -> Vec<String> {
+    unknown(&SETTINGS_SCHEMA, table.clone())
+}
+
+fn unknown(schema: &Value, table: toml::Table) -> Vec<String> {
+    let mut issues = vec![];
+    check(schema, schema, &toml::Value::Table(table), "", &mut issues);
+    issues
+}
+
+// schema properties and the schema of additional properties
+// collected from the schema and its subschemas
+#[derive(Default)]
+struct Properties<'a> {
+    properties: Map<String, Value>,
+    additional: Option<&'a Value>,
+    items: Vec<&'a Value>,
+    fixed: bool,
+}
+
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix("#/definitions/"))
+        .and_then(|name| root.get("definitions").and_then(|d| d.get(name)))
+        .map(|schema| resolve(root, schema))
+        .unwrap_or(schema)
+}
+
+fn collect<'a>(root: &'a Value, schema: &'a Value, properties: &mut Properties<'a>) {
+    let schema = resolve(root, schema);
+
+    if let Some(object) = schema.get("properties").and_then(Value::as_object) {
+        properties.fixed = true;
+        for (key, value) in object {
+            properties.properties.insert(key.clone(), value.clone());
+        }
+    }
+    if let Some(additional) = schema.get("additionalProperties").filter(|v| v.is_object()) {
+        properties.additional = Some(additional);
+    }
+    if let Some(items) = schema.get("items") {
+        properties.items.push(items);
+    }
+
+    for combinator in ["allOf", "anyOf", "oneOf"] {
+        for schema in schema
+            .get(combinator)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            collect(root, schema, properties);
+        }
+    }
+}
+
+fn check(root: &Value, schema: &Value, value: &toml::Value, path: &str, issues: &mut Vec<String>) {
+    let mut properties = Properties::default();
+    collect(root, schema, &mut properties);
+
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                if let Some(schema) = properties.properties.get(key) {
+                    check(root, schema, value, &path, issues);
+                } else if let Some(schema) = properties.additional {
+                    check(root, schema, value, &path, issues);
+                } else if properties.fixed {
+                    let issue = match suggest(key, properties.properties.keys()) {
+                        Some(known) => format!("unknown key `{path}` (did you mean `{known}`?)"),
+                        None => format!("unknown key `{path}`"),
+                    };
+                    issues.push(issue);
+                }
+            }
+        }
+        toml::Value::Array(array) => {
+            for (index, value) in array.iter().enumerate() {
+                for schema in properties.items.iter() {
+                    check(root, schema, value, &format!("{path}[{index}]"), issues);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn suggest<'a>(key: &str, known: impl Iterator<Item = &'a String>) -> Option<&'a str> {
+    known
+        .map(|candidate| {
+            (
+                candidate,
+                strsim::normalized_damerau_levenshtein(key, candidate),
+            )
+        })
+        .filter(|(_, similarity)| *similarity >= SUGGESTION_THRESHOLD)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _)| candidate.as_str())
+}
@@ -0,0 +1,293 @@
+//!
+//! Layered runtime settings. Each layer overrides the previous one:
+//!
+//! 1. compiled defaults (`Resolver.toml`)
+//! 2. `~/.kaspa-resolver/settings.toml` or the `--settings` file
+//! 3. `KASPA_RESOLVER_*` environment variables
+//! 4. `--set <key>=<value>` command line flags
+//!
+//! Environment variable names are derived from the setting path,
+//! e.g. `sync.poll-sec` becomes `KASPA_RESOLVER_SYNC_POLL_SEC`.
+//! Unknown keys are reported as warnings (rejected if `config.strict`
+//! is enabled).
+//!
+
+use crate::imports::*;
+use std::collections::BTreeMap;
+use toml::{Table, Value};
+
+pub const DEFAULTS: &str = include_str!("../Resolver.toml");
+pub const ENV_PREFIX: &str = "KASPA_RESOLVER_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Default,
+    File,
+    Env,
+    Cli,
+}
+
+impl Display for Layer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let layer = match self {
+            Layer::Default => "default",
+            Layer::File => "file",
+            Layer::Env => "env",
+            Layer::Cli => "cli",
+        };
+        f.write_str(layer)
+    }
+}
+
+/// Merged settings table with the layer each value originates from.
+#[derive(Debug)]
+pub struct Layered {
+    table: Table,
+    layers: BTreeMap<String, Layer>,
+}
+
+impl Layered {
+    pub fn load(file: Option<&Path>, overrides: &[String]) -> Result<Self> {
+        let mut layered = Self {
+            table: Table::new(),
+            layers: BTreeMap::new(),
+        };
+
+        layered.merge(toml::from_str(DEFAULTS)?, Layer::Default);
+
+        let file = match file {
+            Some(file) if !file.exists() => {
+                return Err(Error::config(format!(
+                    "Settings file not found: `{}`",
+                    file.display()
+                )));
+            }
+            Some(file) => Some(file.to_path_buf()),
+            None => Some(global_config_folder().join("settings.toml")).filter(|f| f.exists()),
+        };
+
+        if let Some(file) = file {
+            log_info!("Settings", "Using settings file: `{}`", file.display());
+            let toml = std::fs::read_to_string(&file).map_err(|err| Error::file(&file, err))?;
+            let table = toml::from_str::<Table>(&toml).map_err(|err| {
+                Error::config(format!("Invalid settings file `{}`: {err}", file.display()))
+            })?;
+            layered.merge(table, Layer::File);
+        }
+
+        layered.apply_env()?;
+        layered.apply_overrides(overrides)?;
+
+        Ok(layered)
+    }
+
+    /// Typed settings produced from the merged table.
+    pub fn settings(&self) -> Result<Settings> {
+        Value::Table(self.table.clone())
+            .try_into::<Settings>()
+            .map_err(|err| Error::config(format!("Invalid settings: {err}")))
+    }
+
+    // reports keys not recognized by the resolver; these can only
+    // originate from the settings file or `--set` overrides as
+    // environment variables are matched against known settings
+    fn check_unknown(&self) -> Result<()> {
+        let unknown = schema::unknown_settings(&self.table);
+        let strict = self
+            .get("config.strict")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if strict && !unknown.is_empty() {
+            return Err(Error::config(format!(
+                "Invalid settings: {}",
+                unknown.join(", ")
+            )));
+        }
+        for issue in unknown.iter() {
+            log_warn!("Settings", "Ignoring {issue}");
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, table: Table, layer: Layer) {
+        let mut path = vec![];
+        merge_table(&mut self.table, table, layer, &mut path, &mut self.layers);
+    }
+
+    fn apply_env(&mut self) -> Result<()> {
+        let leaves = leaves(&self.table, "");
+        let mut known = HashSet::new();
+        for (path, current) in leaves {
+            let name = env_name(&path);
+            if let Ok(raw) = std::env::var(&name) {
+                let value = parse_value(&path, &raw, Some(&current))?;
+                self.set(&path, value, Layer::Env)?;
+            }
+            known.insert(name);
+        }
+
+        for (name, _) in std::env::vars() {
+            if name.starts_with(ENV_PREFIX) && !known.contains(&name) {
+                log_warn!("Settings", "Ignoring unknown environment variable `{name}`");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_overrides(&mut self, overrides: &[String]) -> Result<()> {
+        for entry in overrides {
+            let (path, raw) = entry.split_once('=').ok_or_else(|| {
+                Error::config(format!(
+                    "Invalid setting override `{entry}`, expected `<key>=<value>`"
+                ))
+            })?;
+            let path = path.trim();
+            let current = self.get(path).cloned();
+            let value = parse_value(path, raw.trim(), current.as_ref())?;
+            self.set(path, value, Layer::Cli)?;
+        }
+        Ok(())
+    }
+
+    fn get(&self, path: &str) -> Option<&Value> {
+        let mut parts = path.split('.');
+        let mut value = self.table.get(parts.next()?)?;
+        for part in parts {
+            value = value.as_table()?.get(part)?;
+        }
+        Some(value)
+    }
+
+    fn set(&mut self, path: &str, value: Value, layer: Layer) -> Result<()> {
+        let mut parts = path.split('.').collect::<Vec<_>>();
+        let key = parts
+            .pop()
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| Error::config(format!("Invalid setting key `{path}`")))?;
+
+        let mut table = &mut self.table;
+        for part in parts {
+            table = table
+                .entry(part)
+                .or_insert_with(|| Value::Table(Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| Error::config(format!("Invalid setting key `{path}`")))?;
+        }
+        table.insert(key.to_string(), value);
+        self.layers.insert(path.to_string(), layer);
+        Ok(())
+    }
+
+    /// Effective settings, one `key = value` line per setting
+    /// annotated with the originating layer.
+    pub fn render(&self) -> String {
+        leaves(&self.table, "")
+            .into_iter()
+            .map(|(path, value)| {
+                let layer = self.layers.get(&path).copied().unwrap_or(Layer::Default);
+                format!("{path} = {value}  # {layer}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn merge_table(
+    target: &mut Table,
+    source: Table,
+    layer: Layer,
+    path: &mut Vec<String>,
+    layers: &mut BTreeMap<String, Layer>,
+) {
+    for (key, value) in source {
+        path.push(key.clone());
+        let value = match (target.get_mut(&key), value) {
+            (Some(Value::Table(target)), Value::Table(source)) => {
+                merge_table(target, source, layer, path, layers);
+                None
+            }
+            (_, value) => Some(value),
+        };
+        if let Some(value) = value {
+            for (leaf, _) in leaves_of(&value, &path.join(".")) {
+                layers.insert(leaf, layer);
+            }
+            target.insert(key, value);
+        }
+        path.pop();
+    }
+}
+
+// leaf values of a table (arrays are treated as leaves)
+fn leaves(table: &Table, prefix: &str) -> Vec<(String, Value)> {
+    table
+        .iter()
+        .flat_map(|(key, value)| {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            leaves_of(value, &path)
+        })
+        .collect()
+}
+
+fn leaves_of(value: &Value, path: &str) -> Vec<(String, Value)> {
+    match value {
+        Value::Table(table) => leaves(table, path),
+        value => vec![(path.to_string(), value.clone())],
+    }
+}
+
+fn env_name(path: &str) -> String {
+    format!(
+        "{ENV_PREFIX}{}",
+        path.replace(['.', '-'], "_").to_uppercase()
+    )
+}
+
+// parse a raw value as a TOML value, using the type of the
+// current value (if any) to coerce integers and plain strings
+fn parse_value(path: &str, raw: &str, current: Option<&Value>) -> Result<Value> {
+    let parsed = toml::from_str::<Table>(&format!("value = {raw}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"));
+
+    let mismatch = || {
+        Error::config(format!(
+            "Invalid value `{raw}` for setting `{path}` (expected {})",
+            current.map(Value::type_str).unwrap_or("value")
+        ))
+    };
+
+    match (current, parsed) {
+        (Some(Value::Float(_)), Some(Value::Integer(int))) => Ok(Value::Float(int as f64)),
+        (Some(Value::String(_)), Some(Value::String(string))) => Ok(Value::String(string)),
+        (Some(Value::String(_)), _) | (None, None) => Ok(Value::String(raw.to_string())),
+        (Some(current), Some(value)) if current.same_type(&value) => Ok(value),
+        (None, Some(value)) => Ok(value),
+        _ => Err(mismatch()),
+    }
+}
+
+static LAYERED: OnceLock<Layered> = OnceLock::new();
+
+pub fn init(file: Option<&Path>, overrides: &[String]) -> Result<Settings> {
+    let layered = Layered::load(file, overrides)?;
+    let settings = layered.settings()?;
+    layered.check_unknown()?;
+    settings.validate()?;
+    LAYERED.set(layered).ok();
+    Ok(settings)
+}
+
+/// Prints effective settings (`resolver settings`).
+pub fn print() -> Result<()> {
+    let layered = LAYERED
+        .get()
+        .ok_or_else(|| Error::custom("Settings are not initialized"))?;
+    println!("{}", layered.render());
+    Ok(())
+}
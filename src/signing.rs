@@ -0,0 +1,124 @@
+//!
+//! Detached Ed25519 signatures of configuration packages.
+//!
+//! Packages are signed by the publisher key (separate from the
+//! package encryption key) and verified by resolvers against the
+//! list of trusted publisher public keys (`updates.trusted-keys`).
+//!
+
+use crate::imports::*;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+fn publisher_key_file() -> PathBuf {
+    global_config_folder().join(".publisher")
+}
+
+pub fn load_publisher_key() -> Result<SigningKey> {
+    let path = publisher_key_file();
+    if !path.exists() {
+        return Err(Error::KeyNotFound);
+    }
+    let bytes = std::fs::read(&path).map_err(|err| Error::file(&path, err))?;
+    let bytes: [u8; 32] = bytes.as_slice().try_into()?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+pub fn generate_publisher_key() -> Result<()> {
+    let path = publisher_key_file();
+    if path.exists() && !cliclack::confirm("Publisher key already exists. Overwrite?").interact()? {
+        return Ok(());
+    }
+
+    let signing_key = SigningKey::from_bytes(&rand::thread_rng().gen::<[u8; 32]>());
+    write_private(&path, &signing_key.to_bytes()).map_err(|err| Error::file(&path, err))?;
+    log::success(format!(
+        "Publisher public key: {}",
+        signing_key.verifying_key().to_bytes().to_vec().to_hex()
+    ))?;
+    log::info(
+        "Add this key to `updates.trusted-keys` to accept packages signed by this publisher",
+    )?;
+    Ok(())
+}
+
+/// Writes the file readable by the owner only.
+pub fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // restrict permissions of a previously created file
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(data)
+}
+
+pub fn get_publisher_key() -> Result<SigningKey> {
+    match load_publisher_key() {
+        Ok(key) => Ok(key),
+        Err(_) => {
+            generate_publisher_key()?;
+            load_publisher_key()
+        }
+    }
+}
+
+/// Produces a detached signature of the package data.
+pub fn sign(signing_key: &SigningKey, data: &[u8]) -> Vec<u8> {
+    signing_key.sign(data).to_bytes().to_vec()
+}
+
+fn trusted_keys() -> Result<Vec<VerifyingKey>> {
+    Updates::trusted_keys()
+        .iter()
+        .map(|hex| {
+            let bytes = <[u8; 32]>::from_hex(hex.trim())
+                .map_err(|_| Error::config(format!("Invalid trusted key: `{hex}`")))?;
+            VerifyingKey::from_bytes(&bytes)
+                .map_err(|_| Error::config(format!("Invalid trusted key: `{hex}`")))
+        })
+        .collect()
+}
+
+/// Verifies the detached package signature against trusted publisher
+/// keys. If no trusted keys are configured, verification is skipped
+/// (with a warning) unless `updates.allow-unsigned` is disabled, in
+/// which case packages are rejected. Returns the hex representation
+/// of the signing key (if verified).
+pub fn verify(data: &[u8], signature: Option<&[u8]>) -> Result<Option<String>> {
+    let trusted_keys = trusted_keys()?;
+    if trusted_keys.is_empty() {
+        if Updates::allow_unsigned() {
+            log_warn!(
+                "Config",
+                "!!! No trusted publisher keys configured (`updates.trusted-keys`)"
+            );
+            log_warn!(
+                "Config",
+                "!!! Accepting package without signature verification"
+            );
+            return Ok(None);
+        }
+        return Err(Error::Signature(
+            "no trusted publisher keys configured (`updates.trusted-keys`)",
+        ));
+    }
+
+    let signature = signature.ok_or(Error::Signature("missing package signature"))?;
+    let signature =
+        Signature::from_slice(signature).map_err(|_| Error::Signature("invalid signature"))?;
+
+    trusted_keys
+        .iter()
+        .find(|key| key.verify(data, &signature).is_ok())
+        .map(|key| Some(key.to_bytes().to_vec().to_hex()))
+        .ok_or(Error::Signature(
+            "package is not signed by a trusted publisher",
+        ))
+}
@@ -0,0 +1,196 @@
+//!
+//! Configuration update sources.
+//!
+//! Packages are fetched from the primary update URL followed by
+//! an ordered list of mirrors (`https://` URLs or `file://` directories).
+//! A source is skipped if it is unreachable, times out or serves
+//! a package that fails signature verification.
+//!
+
+use crate::imports::*;
+use std::time::SystemTime;
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct UpdateSource {
+    pub url: String,
+    pub timeout_sec: Option<f64>,
+}
+
+impl Display for UpdateSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.url)
+    }
+}
+
+impl UpdateSource {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            timeout_sec: None,
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout_sec
+            .map(Duration::from_secs_f64)
+            .unwrap_or_else(Updates::timeout)
+    }
+
+    /// Fetches a file from the source. Returns `None` if the
+    /// file does not exist.
+    async fn fetch(&self, file: &str) -> Result<Option<Vec<u8>>> {
+        if let Some(folder) = self.url.strip_prefix("file://") {
+            let path = PathBuf::from(folder).join(file);
+            tokio::task::spawn_blocking(move || match std::fs::read(&path) {
+                Ok(data) => Ok(Some(data)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(Error::file(&path, err)),
+            })
+            .await
+            .map_err(|err| Error::custom(format!("Unable to read `{self}`: {err}")))?
+        } else if self.url.starts_with("https://") || self.url.starts_with("http://") {
+            let url = self.join(file)?;
+            let response = reqwest::Client::builder()
+                .timeout(self.timeout())
+                .build()?
+                .get(url)
+                .send()
+                .await?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                Ok(None)
+            } else {
+                Ok(Some(response.error_for_status()?.bytes().await?.to_vec()))
+            }
+        } else {
+            Err(Error::config(format!(
+                "Unsupported update source: `{}`",
+                self.url
+            )))
+        }
+    }
+
+    // URL of the file relative to the source URL (treated as
+    // a folder regardless of a trailing `/`)
+    fn join(&self, file: &str) -> Result<reqwest::Url> {
+        let base = if self.url.ends_with('/') {
+            self.url.clone()
+        } else {
+            format!("{}/", self.url)
+        };
+        reqwest::Url::parse(&base)
+            .and_then(|base| base.join(file))
+            .map_err(|err| Error::config(format!("Invalid update source `{}`: {err}", self.url)))
+    }
+
+    async fn fetch_package(&self) -> Result<Package> {
+        let data = self
+            .fetch(&global_config_file())
+            .await?
+            .ok_or_else(|| Error::custom(format!("Package not found at `{self}`")))?;
+
+        if data.len() < 24 {
+            return Err(Error::custom(format!(
+                "Update: invalid data length: {}",
+                data.len()
+            )));
+        }
+
+        let signature = self.fetch(&global_signature_file()).await?;
+        let publisher = signing::verify(&data, signature.as_deref())?;
+        let hash = sha256(data.as_slice()).as_slice().to_vec().to_hex();
+
+        Ok(Package {
+            data,
+            signature,
+            publisher,
+            hash,
+            source: self.url.clone(),
+        })
+    }
+}
+
+pub struct Package {
+    pub data: Vec<u8>,
+    pub signature: Option<Vec<u8>>,
+    pub publisher: Option<String>,
+    pub hash: String,
+    pub source: String,
+}
+
+/// Fetches the configuration package from the first source that
+/// serves a valid package. If `updates.cross-check` is enabled,
+/// the package hash is compared against all remaining reachable
+/// sources and the update is refused if any of them disagrees.
+pub async fn fetch_package() -> Result<Package> {
+    let sources = Updates::sources();
+
+    let mut package = None;
+    let mut remaining = sources.iter();
+    for source in remaining.by_ref() {
+        match source.fetch_package().await {
+            Ok(p) => {
+                package = Some(p);
+                break;
+            }
+            Err(err) => {
+                log_warn!("Update", "Source `{source}` failed: {err}");
+            }
+        }
+    }
+
+    let package = package.ok_or_else(|| Error::custom("All update sources failed"))?;
+
+    if Updates::cross_check() {
+        for source in remaining {
+            match source.fetch(&global_config_file()).await {
+                Ok(Some(data)) => {
+                    let hash = sha256(data.as_slice()).as_slice().to_vec().to_hex();
+                    if hash != package.hash {
+                        return Err(Error::custom(format!(
+                            "Update source `{source}` serves package `{hash}`, expected `{}` (from `{}`)",
+                            package.hash, package.source
+                        )));
+                    }
+                }
+                Ok(None) => {
+                    log_warn!("Update", "Source `{source}`: package not found");
+                }
+                Err(err) => {
+                    log_warn!("Update", "Source `{source}` unreachable: {err}");
+                }
+            }
+        }
+    }
+
+    Ok(package)
+}
+
+/// Update source and package currently in use.
+#[derive(Clone, Debug, Serialize)]
+pub struct ActiveSource {
+    pub source: String,
+    pub hash: String,
+    pub publisher: Option<String>,
+    // unix timestamp (seconds) of the last successful fetch
+    pub ts: u64,
+}
+
+static ACTIVE: Mutex<Option<ActiveSource>> = Mutex::new(None);
+
+pub fn set_active(package: &Package) {
+    let ts = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    ACTIVE.lock().unwrap().replace(ActiveSource {
+        source: package.source.clone(),
+        hash: package.hash.clone(),
+        publisher: package.publisher.clone(),
+        ts,
+    });
+}
+
+pub fn active() -> Option<ActiveSource> {
+    ACTIVE.lock().unwrap().clone()
+}
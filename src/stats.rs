@@ -0,0 +1,155 @@
+use crate::imports::*;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Sliding windows over which RPC statistics are reported.
+pub const WINDOWS: &[(&str, Duration)] = &[
+    ("1m", Duration::from_secs(60)),
+    ("15m", Duration::from_secs(15 * 60)),
+];
+
+// upper bound on samples retained per method
+const MAX_SAMPLES: usize = 4096;
+
+#[derive(Debug)]
+struct Sample {
+    ts: Instant,
+    latency: Duration,
+    error: Option<&'static str>,
+}
+
+#[derive(Debug, Default)]
+struct MethodStats {
+    total_calls: u64,
+    total_errors: u64,
+    samples: VecDeque<Sample>,
+}
+
+impl MethodStats {
+    fn record(&mut self, latency: Duration, error: Option<&'static str>) {
+        let ts = Instant::now();
+        self.total_calls += 1;
+        if error.is_some() {
+            self.total_errors += 1;
+        }
+        self.samples.push_back(Sample { ts, latency, error });
+        self.prune(ts);
+    }
+
+    fn prune(&mut self, now: Instant) {
+        let retention = WINDOWS
+            .iter()
+            .map(|(_, window)| *window)
+            .max()
+            .unwrap_or_default();
+        while self
+            .samples
+            .front()
+            .is_some_and(|sample| now.duration_since(sample.ts) > retention)
+            || self.samples.len() > MAX_SAMPLES
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    fn snapshot(&self, now: Instant) -> MethodSnapshot {
+        let windows = WINDOWS
+            .iter()
+            .map(|(name, window)| {
+                let samples = self
+                    .samples
+                    .iter()
+                    .filter(|sample| now.duration_since(sample.ts) <= *window)
+                    .collect::<Vec<_>>();
+                (*name, WindowSnapshot::from_samples(samples.as_slice()))
+            })
+            .collect();
+
+        MethodSnapshot {
+            total_calls: self.total_calls,
+            total_errors: self.total_errors,
+            windows,
+        }
+    }
+}
+
+/// Rolling per-method RPC call statistics of a [`Connection`].
+#[derive(Debug, Default)]
+pub struct Stats {
+    methods: Mutex<AHashMap<&'static str, MethodStats>>,
+}
+
+impl Stats {
+    pub fn record(&self, method: &'static str, latency: Duration, error: Option<&Error>) {
+        self.methods
+            .lock()
+            .unwrap()
+            .entry(method)
+            .or_default()
+            .record(latency, error.map(Error::kind));
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let now = Instant::now();
+        self.methods
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(method, stats)| (*method, stats.snapshot(now)))
+            .collect()
+    }
+}
+
+pub type StatsSnapshot = BTreeMap<&'static str, MethodSnapshot>;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MethodSnapshot {
+    pub total_calls: u64,
+    pub total_errors: u64,
+    pub windows: BTreeMap<&'static str, WindowSnapshot>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WindowSnapshot {
+    pub calls: u64,
+    pub errors: BTreeMap<&'static str, u64>,
+    // latency percentiles in milliseconds
+    pub p50: Option<f64>,
+    pub p95: Option<f64>,
+    pub p99: Option<f64>,
+}
+
+impl WindowSnapshot {
+    fn from_samples(samples: &[&Sample]) -> Self {
+        let mut errors = BTreeMap::<&'static str, u64>::new();
+        for kind in samples.iter().filter_map(|sample| sample.error) {
+            *errors.entry(kind).or_default() += 1;
+        }
+
+        let mut latencies = samples
+            .iter()
+            .map(|sample| sample.latency)
+            .collect::<Vec<_>>();
+        latencies.sort_unstable();
+
+        Self {
+            calls: samples.len() as u64,
+            errors,
+            p50: percentile(&latencies, 0.50),
+            p95: percentile(&latencies, 0.95),
+            p99: percentile(&latencies, 0.99),
+        }
+    }
+}
+
+// nearest-rank percentile over sorted latencies, in milliseconds
+fn percentile(sorted: &[Duration], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        None
+    } else {
+        let rank = (p * sorted.len() as f64).ceil() as usize;
+        let index = rank.clamp(1, sorted.len()) - 1;
+        Some(sorted[index].as_secs_f64() * 1000.0)
+    }
+}
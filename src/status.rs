@@ -27,7 +27,7 @@ pub async fn logout_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> imp
     Redirect::to("/status").into_response()
 }
 
-fn validate_passphrase(passphrase: &str) -> bool {
+pub fn validate_passphrase(passphrase: &str) -> bool {
     static KEY: OnceLock<Option<u64>> = OnceLock::new();
     let key = KEY.get_or_init(|| load_key64().ok());
     if let Some(key64) = key {
@@ -178,6 +178,15 @@ pub struct Status<'a> {
     pub sid: u64,
     #[serde(with = "SerHex::<Strict>")]
     pub uid: u64,
+    // explicit node id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<&'a str>,
+    // address-derived uid this node has migrated from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub legacy_uid: Option<String>,
+    // node source the node originates from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Provenance>,
     pub url: &'a str,
     pub fqdn: &'a str,
     pub service: String,
@@ -192,8 +201,21 @@ pub struct Status<'a> {
     pub peers: u64,
     pub clients: u64,
     pub capacity: u64,
+    pub weight: f64,
+    pub tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rss: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fds: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drain: Option<DrainInfo>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub delegates: Option<Vec<String>>,
+    pub stats: StatsSnapshot,
 }
 
 impl<'a> From<&'a Arc<Connection>> for Status<'a> {
@@ -202,6 +224,12 @@ impl<'a> From<&'a Arc<Connection>> for Status<'a> {
 
         let node = connection.node();
         let uid = node.uid();
+        let id = node.id();
+        let legacy_uid = connection
+            .legacy_uid()
+            .filter(|legacy_uid| *legacy_uid != uid)
+            .map(|legacy_uid| format!("{legacy_uid:016x}"));
+        let source = node.provenance();
         let url = node.address.as_str();
         let fqdn = node.fqdn.as_str();
         let service = node.service().to_string();
@@ -227,16 +255,29 @@ impl<'a> From<&'a Arc<Connection>> for Status<'a> {
             })
             .unwrap_or_else(|| ("n/a".to_string(), 0, 0, 0, 0));
 
+        let metrics = delegate.metrics();
+        let cpu = metrics.as_ref().map(|metrics| metrics.cpu_usage);
+        let rss = metrics.as_ref().map(|metrics| metrics.resident_set_size);
+        let fds = metrics.as_ref().map(|metrics| metrics.fd_num);
+        let pressure = delegate.pressure();
+        let weight = node.weight();
+        let tags = node.tags();
+        let drain = connection.drain();
+
         let delegates = connection
             .resolve_delegators()
             .iter()
             .map(|connection| format!("[{:016x}] {}", connection.system_id(), connection.address()))
             .collect::<Vec<String>>();
         let delegates = (!delegates.is_empty()).then_some(delegates);
+        let stats = connection.stats().snapshot();
 
         Self {
             sid,
             uid,
+            id,
+            legacy_uid,
+            source,
             version,
             fqdn,
             service,
@@ -251,7 +292,15 @@ impl<'a> From<&'a Arc<Connection>> for Status<'a> {
             clients,
             peers,
             capacity,
+            weight,
+            tags,
+            cpu,
+            rss,
+            fds,
+            pressure,
+            drain,
             delegates,
+            stats,
         }
     }
 }
@@ -1,3 +1,16 @@
+//!
+//! Address templates.
+//!
+//! Templates are compiled when the config is loaded and support:
+//!
+//! - variables: `${fqdn}`
+//! - default values: `${port:-17110}`
+//! - filters: `${fqdn|lowercase}`, `${fqdn|strip_suffix:.example.com}`
+//! - conditionals: `${if tls}wss${else}ws${end}`
+//!
+//! Missing variables (without a default value) are errors.
+//!
+
 use crate::imports::*;
 
 #[derive(Default, Debug, Clone)]
@@ -20,7 +33,6 @@ where
 }
 
 impl Tpl {
-    #[allow(dead_code)]
     pub fn insert<K, V>(&mut self, key: K, value: V)
     where
         K: Display,
@@ -29,26 +41,426 @@ impl Tpl {
         self.map.insert(key.to_string(), value.to_string());
     }
 
-    pub fn render<S>(&self, template: S) -> String
-    where
-        S: Display,
-    {
-        let re = regex::Regex::new(r"\$\{\s*([a-zA-Z0-9_]+)\s*\}").unwrap();
-        let template = template.to_string();
-        let mut result = template.clone();
-
-        for caps in re.captures_iter(template.as_str()) {
-            if let Some(var_name) = caps.get(1) {
-                let key = var_name.as_str().trim();
-                if let Some(value) = self.map.get(key) {
-                    let re = regex::Regex::new(&format!(r"\$\{{\s*{}\s*\}}", key)).unwrap();
-                    result = re.replace(result.as_str(), value.as_str()).to_string();
-                } else {
-                    log_error!("Tpl", "Missing variable: {}", key);
+    fn get(&self, key: &str) -> Option<&str> {
+        self.map
+            .get(key)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    Lowercase,
+    StripSuffix(String),
+    StripPrefix(String),
+}
+
+impl Filter {
+    fn parse(spec: &str) -> Result<Self> {
+        let (name, arg) = match spec.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg)),
+            None => (spec.trim(), None),
+        };
+        match (name, arg) {
+            ("lowercase", None) => Ok(Filter::Lowercase),
+            ("strip_suffix", Some(arg)) => Ok(Filter::StripSuffix(arg.to_string())),
+            ("strip_prefix", Some(arg)) => Ok(Filter::StripPrefix(arg.to_string())),
+            _ => Err(Error::config(format!("Unknown template filter `{spec}`"))),
+        }
+    }
+
+    fn apply(&self, value: String) -> String {
+        match self {
+            Filter::Lowercase => value.to_lowercase(),
+            Filter::StripSuffix(suffix) => value
+                .strip_suffix(suffix.as_str())
+                .map(str::to_string)
+                .unwrap_or(value),
+            Filter::StripPrefix(prefix) => value
+                .strip_prefix(prefix.as_str())
+                .map(str::to_string)
+                .unwrap_or(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Var {
+        name: String,
+        default: Option<String>,
+        filters: Vec<Filter>,
+    },
+    If {
+        name: String,
+        then: Vec<Segment>,
+        otherwise: Vec<Segment>,
+    },
+}
+
+/// Compiled template.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl TryFrom<String> for Template {
+    type Error = Error;
+
+    fn try_from(source: String) -> Result<Self> {
+        Template::parse(&source)
+    }
+}
+
+impl From<Template> for String {
+    fn from(template: Template) -> Self {
+        template.source
+    }
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        Template::parse(source)
+    }
+}
+
+// condition block being parsed
+struct Block {
+    name: String,
+    then: Vec<Segment>,
+    otherwise: Option<Vec<Segment>>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self> {
+        let invalid =
+            |reason: &str| Error::config(format!("Invalid template `{source}`: {reason}"));
+
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut segments = Vec::new();
+        let mut rest = source;
+
+        // segment list of the innermost block
+        fn current<'a>(
+            segments: &'a mut Vec<Segment>,
+            blocks: &'a mut [Block],
+        ) -> &'a mut Vec<Segment> {
+            match blocks.last_mut() {
+                Some(Block {
+                    otherwise: Some(otherwise),
+                    ..
+                }) => otherwise,
+                Some(Block { then, .. }) => then,
+                None => segments,
+            }
+        }
+
+        while let Some(start) = rest.find("${") {
+            if start > 0 {
+                current(&mut segments, &mut blocks).push(Segment::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| invalid("unterminated `${`"))?;
+            let expr = rest[start + 2..start + end].trim();
+            rest = &rest[start + end + 1..];
+
+            if let Some(name) = expr.strip_prefix("if ") {
+                blocks.push(Block {
+                    name: parse_name(name.trim()).map_err(|err| invalid(&err))?,
+                    then: vec![],
+                    otherwise: None,
+                });
+            } else if expr == "else" {
+                let block = blocks
+                    .last_mut()
+                    .ok_or_else(|| invalid("`else` without `if`"))?;
+                if block.otherwise.is_some() {
+                    return Err(invalid("duplicate `else`"));
+                }
+                block.otherwise = Some(vec![]);
+            } else if expr == "end" {
+                let Block {
+                    name,
+                    then,
+                    otherwise,
+                } = blocks.pop().ok_or_else(|| invalid("`end` without `if`"))?;
+                current(&mut segments, &mut blocks).push(Segment::If {
+                    name,
+                    then,
+                    otherwise: otherwise.unwrap_or_default(),
+                });
+            } else {
+                let segment = parse_var(expr).map_err(|err| invalid(&err))?;
+                current(&mut segments, &mut blocks).push(segment);
+            }
+        }
+
+        if !blocks.is_empty() {
+            return Err(invalid("`if` without `end`"));
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    /// Ensures the template refers only to the given variables.
+    pub fn validate(&self, variables: &[&str]) -> Result<()> {
+        let mut names = vec![];
+        collect_names(&self.segments, &mut names);
+        match names.into_iter().find(|name| !variables.contains(name)) {
+            Some(name) => Err(Error::config(format!(
+                "Unknown template variable `{name}` in `{}`",
+                self.source
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Indicates if the template uses the variable without a default
+    /// value (outside of a conditional block testing the variable).
+    pub fn requires(&self, variable: &str) -> bool {
+        fn requires(segments: &[Segment], variable: &str) -> bool {
+            segments.iter().any(|segment| match segment {
+                Segment::Var { name, default, .. } => name == variable && default.is_none(),
+                Segment::If {
+                    name,
+                    then,
+                    otherwise,
+                } => {
+                    (name != variable && requires(then, variable)) || requires(otherwise, variable)
                 }
+                Segment::Text(_) => false,
+            })
+        }
+        requires(&self.segments, variable)
+    }
+
+    pub fn render(&self, tpl: &Tpl) -> Result<String> {
+        let mut result = String::new();
+        render(&self.segments, tpl, &mut result).map_err(|err| {
+            Error::config(format!(
+                "Unable to render template `{}`: {err}",
+                self.source
+            ))
+        })?;
+        Ok(result)
+    }
+}
+
+fn parse_name(name: &str) -> std::result::Result<String, String> {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(name.to_string())
+    } else {
+        Err(format!("invalid variable name `{name}`"))
+    }
+}
+
+fn parse_var(expr: &str) -> std::result::Result<Segment, String> {
+    let mut parts = expr.split('|');
+    let head = parts.next().unwrap_or_default();
+    let (name, default) = match head.split_once(":-") {
+        Some((name, default)) => (name.trim(), Some(default.to_string())),
+        None => (head.trim(), None),
+    };
+    let filters = parts
+        .map(Filter::parse)
+        .collect::<Result<Vec<_>>>()
+        .map_err(|err| match err {
+            Error::Config(msg) => msg,
+            err => err.to_string(),
+        })?;
+
+    Ok(Segment::Var {
+        name: parse_name(name)?,
+        default,
+        filters,
+    })
+}
+
+fn collect_names<'a>(segments: &'a [Segment], names: &mut Vec<&'a str>) {
+    for segment in segments {
+        match segment {
+            Segment::Var { name, .. } => names.push(name),
+            Segment::If {
+                name,
+                then,
+                otherwise,
+            } => {
+                names.push(name);
+                collect_names(then, names);
+                collect_names(otherwise, names);
+            }
+            Segment::Text(_) => {}
+        }
+    }
+}
+
+fn render(segments: &[Segment], tpl: &Tpl, result: &mut String) -> std::result::Result<(), String> {
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => result.push_str(text),
+            Segment::Var {
+                name,
+                default,
+                filters,
+            } => {
+                let value = tpl
+                    .get(name)
+                    .or(default.as_deref())
+                    .ok_or_else(|| format!("missing variable `{name}`"))?;
+                let value = filters
+                    .iter()
+                    .fold(value.to_string(), |value, filter| filter.apply(value));
+                result.push_str(&value);
+            }
+            Segment::If {
+                name,
+                then,
+                otherwise,
+            } => {
+                let truthy = tpl
+                    .get(name)
+                    .is_some_and(|value| value != "false" && value != "0");
+                render(if truthy { then } else { otherwise }, tpl, result)?;
             }
         }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str, vars: &[(&str, &str)]) -> Result<String> {
+        Template::parse(source)?.render(&Tpl::from(vars))
+    }
+
+    #[test]
+    fn render_variables() {
+        let vars = [("fqdn", "n1.example.com"), ("network", "mainnet")];
+        assert_eq!(
+            render("wss://${fqdn}/kaspa/${ network }", &vars).unwrap(),
+            "wss://n1.example.com/kaspa/mainnet"
+        );
+        assert_eq!(render("plain", &vars).unwrap(), "plain");
+    }
+
+    #[test]
+    fn render_defaults() {
+        let source = "${fqdn}:${port:-17110}";
+        assert_eq!(render(source, &[("fqdn", "a")]).unwrap(), "a:17110");
+        assert_eq!(
+            render(source, &[("fqdn", "a"), ("port", "443")]).unwrap(),
+            "a:443"
+        );
+        // empty values fall back to the default
+        assert_eq!(
+            render(source, &[("fqdn", "a"), ("port", "")]).unwrap(),
+            "a:17110"
+        );
+    }
+
+    #[test]
+    fn render_filters() {
+        let vars = [("fqdn", "N1.Example.COM")];
+        assert_eq!(
+            render("${fqdn|lowercase|strip_suffix:.example.com}", &vars).unwrap(),
+            "n1"
+        );
+        assert_eq!(
+            render("${fqdn|strip_prefix:N1.}", &vars).unwrap(),
+            "Example.COM"
+        );
+        // filters apply to default values
+        assert_eq!(render("${host:-LOCAL|lowercase}", &[]).unwrap(), "local");
+    }
+
+    #[test]
+    fn render_conditionals() {
+        let source = "${if tls}wss${else}ws${end}://${fqdn}";
+        for (tls, scheme) in [("true", "wss"), ("false", "ws"), ("0", "ws"), ("", "ws")] {
+            let vars = [("tls", tls), ("fqdn", "a")];
+            assert_eq!(render(source, &vars).unwrap(), format!("{scheme}://a"));
+        }
+        assert_eq!(render(source, &[("fqdn", "a")]).unwrap(), "ws://a");
+
+        let nested = "${if a}A${if b}B${end}${else}-${end}";
+        assert_eq!(render(nested, &[("a", "1"), ("b", "1")]).unwrap(), "AB");
+        assert_eq!(render(nested, &[("a", "1")]).unwrap(), "A");
+        assert_eq!(render(nested, &[]).unwrap(), "-");
+    }
+
+    #[test]
+    fn render_rejects_missing_variables() {
+        let err = render("${fqdn}", &[]).unwrap_err();
+        assert!(err.to_string().contains("missing variable `fqdn`"));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_templates() {
+        for source in [
+            "${fqdn",
+            "${}",
+            "${fq-dn}",
+            "${fqdn|uppercase}",
+            "${fqdn|strip_suffix}",
+            "${if tls}wss",
+            "${else}",
+            "${end}",
+            "${if tls}a${else}b${else}c${end}",
+        ] {
+            assert!(Template::parse(source).is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn validate_variables() {
+        let template = Template::parse("${if tls}wss${end}://${fqdn|lowercase}").unwrap();
+        assert!(template.validate(&["tls", "fqdn"]).is_ok());
+        let err = template.validate(&["fqdn"]).unwrap_err();
+        assert!(err.to_string().contains("Unknown template variable `tls`"));
+    }
+
+    #[test]
+    fn requires_variables() {
+        let template =
+            Template::parse("${if port}:${port}${end}/${fqdn}/${path:-x}${if tls}${id}${end}")
+                .unwrap();
+        assert!(template.requires("fqdn"));
+        assert!(template.requires("id"));
+        assert!(!template.requires("port"));
+        assert!(!template.requires("path"));
+        assert!(!template.requires("tls"));
+    }
 
-        result
+    #[test]
+    fn serde_round_trip() {
+        let source = "${if tls}wss${else}ws${end}://${fqdn}";
+        let template: Template = serde_json::from_str(&format!("{source:?}")).unwrap();
+        assert_eq!(template.to_string(), source);
+        assert_eq!(
+            serde_json::to_string(&template).unwrap(),
+            format!("{source:?}")
+        );
+        assert!(serde_json::from_str::<Template>("\"${fqdn\"").is_err());
     }
 }
@@ -70,7 +70,18 @@ impl Display for EncodingKind {
 }
 
 #[derive(
-    Debug, Describe, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+    Debug,
+    Describe,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
 )]
 #[serde(rename_all = "kebab-case")]
 pub enum TransportKind {
@@ -91,6 +102,12 @@ impl Display for TransportKind {
 }
 
 impl TransportKind {
+    /// Transports the resolver is able to connect to
+    /// (gRPC is not currently supported).
+    pub fn is_supported(&self) -> bool {
+        matches!(self, TransportKind::WrpcBorsh | TransportKind::WrpcJson)
+    }
+
     pub fn protocol(&self) -> ProtocolKind {
         match self {
             TransportKind::WrpcBorsh => ProtocolKind::Wrpc,
@@ -116,36 +133,82 @@ pub struct TransportTemplate {
     pub template: String,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Transport {
     #[serde(rename = "type")]
     pub kind: TransportKind,
     pub tls: bool,
-    pub template: String,
+    #[schemars(with = "String")]
+    pub template: Template,
+    // per-network port numbers available to the template as `${port}`
+    #[schemars(with = "Option<HashMap<String, u16>>")]
+    pub ports: Option<HashMap<NetworkId, u16>>,
 }
 
 impl Transport {
-    pub fn make_address(&self, fqdn: &str, service: &Service, network_id: &NetworkId) -> String {
-        // let fqdn = format!("{}/$*/", fqdn);
-        let tpl: Tpl = [
+    /// Variables available to address templates.
+    pub const VARIABLES: &'static [&'static str] = &[
+        "service", "fqdn", "network", "protocol", "encoding", "tls", "port",
+    ];
+
+    pub fn validate(&self) -> Result<()> {
+        self.template.validate(Self::VARIABLES)?;
+        if self.ports.is_none() && self.template.requires("port") {
+            return Err(Error::config(format!(
+                "Template `{}` uses `${{port}}` without a default value or transport `ports` map",
+                self.template
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn make_address(
+        &self,
+        fqdn: &str,
+        service: &Service,
+        network_id: &NetworkId,
+    ) -> Result<String> {
+        let mut tpl: Tpl = [
             ("service", service.to_string()),
             ("fqdn", fqdn.to_string()),
             ("network", network_id.to_string()),
             ("protocol", self.kind.protocol().to_string()),
             ("encoding", self.kind.encoding().to_string()),
+            ("tls", self.tls.to_string()),
         ]
         .as_ref()
         .into();
 
-        tpl.render(&self.template)
+        if let Some(port) = self.ports.as_ref().and_then(|ports| ports.get(network_id)) {
+            tpl.insert("port", port);
+        }
+
+        self.template.render(&tpl)
     }
 }
 
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct TransportDictionary(HashMap<String, Transport>);
 
 impl TransportDictionary {
     pub fn get(&self, key: &str) -> Option<&Transport> {
         self.0.get(key)
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Transport)> {
+        self.0.iter()
+    }
+
+    pub fn insert(&mut self, key: String, transport: Transport) {
+        self.0.insert(key, transport);
+    }
+}
+
+impl IntoIterator for TransportDictionary {
+    type Item = (String, Transport);
+    type IntoIter = std::collections::hash_map::IntoIter<String, Transport>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
 }
@@ -14,7 +14,7 @@ document.addEventListener('DOMContentLoaded', () => {
 
     thead = document.createElement('thead');
     table.appendChild(thead);
-    thead.innerHTML = "<tr><th>SID:UID</th><th>SERVICE</th><th>VERSION</th><th class='fqdn'>FQDN</th><th>PROTO</th><th>ENCODING</th><th>NETWORK</th><th>STATUS</th><th class='right'>PEERS</th><th class='right'>CLIENTS / CAP</th><th class='right'>LOAD</th></tr>";
+    thead.innerHTML = "<tr><th>SID:UID</th><th>SERVICE</th><th>VERSION</th><th class='fqdn'>FQDN</th><th>PROTO</th><th>ENCODING</th><th>NETWORK</th><th>STATUS</th><th class='right'>PEERS</th><th class='right'>CLIENTS / CAP</th><th class='right'>LOAD</th><th class='right'>RTT P95</th><th class='right'>ERRORS</th></tr>";
 
     tbody = document.createElement('tbody');
     tbody.id = "nodes";
@@ -143,6 +143,8 @@ function render() {
             clients,
             capacity,
             delegates,
+            stats,
+            drain,
         } = node;
 
         let el = document.getElementById(uid);
@@ -163,8 +165,19 @@ function render() {
         let clients_ = pad(clients.toLocaleString(),6);
         let capacity_ = pad(capacity.toLocaleString(),6);
         el.innerHTML = `<td>${sid}:${uid}</td><td>${service}</td><td>${version}</td><td class='fqdn'>${fqdn}</td><td>${protocol}</td><td>${encoding}</td><td>${network}</td><td>${status}</td>`;
+        if (drain) {
+            let ts = new Date(drain.ts * 1000).toISOString().replace('T', ' ').substring(0, 19);
+            el.lastChild.title = `drain set by ${drain.by} at ${ts}`;
+            el.lastChild.innerHTML = `${status} (${drain.by} @ ${ts})`;
+        }
         if (status != "offline") {
             el.innerHTML += `<td class='wide right pre'>${peers_}</td><td class='wide right pre'>${clients_} / ${capacity_}</td><td class='wide right'>${load}%</td>`;
+            let sync = stats && stats["get_sync"] && stats["get_sync"].windows["1m"];
+            let rtt = (sync && sync.p95 != null) ? `${sync.p95.toFixed(1)}ms` : "n/a";
+            let errors = Object.values(stats || {})
+                .map((method) => Object.values(method.windows["1m"].errors).reduce((a, b) => a + b, 0))
+                .reduce((a, b) => a + b, 0);
+            el.innerHTML += `<td class='wide right'>${rtt}</td><td class='wide right'>${errors}</td>`;
         }
     });
 